@@ -8,6 +8,7 @@
 //! ## Dependencies
 //!
 //! This pallet depends on an external implementation of its `FetchPrice` trait - for example by an offchain worker - to act as a price oracle.
+//! Alternatively, an oracle account can push prices with the `submit_price` extrinsic which are read by the `StoredPrice` implementation.
 //!
 //! ## Installation
 //!
@@ -45,6 +46,35 @@
 //!     pub const BaseUnit: Coins = 1_000_000;
 //!     pub const InitialSupply: Coins = 1000 * BaseUnit::get();
 //!     pub const MinimumSupply: Coins = BaseUnit::get();
+//!     pub const ContractionCarryover: bool = false;
+//!     pub const MinSeigniorageBalance: Coins = 0;
+//!     pub const MaxLadderSize: u32 = 10;
+//!     pub const SeigniorageFallback: Option<AccountId> = None;
+//!     pub const MaxBidIntakePerBlock: Coins = 1_000_000 * BaseUnit::get();
+//!     pub const MaxShareOfExpansion: Perbill = Perbill::from_percent(100);
+//!     pub const ContractionReserve: Option<AccountId> = None;
+//!     pub const BidCooldown: BlockNumber = 0;
+//!     pub const BondPayoutMode: pallet_stablecoin::BondPayoutMode = pallet_stablecoin::BondPayoutMode::Fifo;
+//!     pub const MaxCoinSupply: Coins = Coins::max_value();
+//!     pub const MinAcceptablePrice: Coins = BaseUnit::get() / 10;
+//!     pub const MaxAcceptablePrice: Coins = 10 * BaseUnit::get();
+//!     pub const BondsExpire: bool = true;
+//!     pub const MaximumBondSize: Coins = 1_000_000 * BaseUnit::get();
+//!     pub const SeigniorageToShareholders: bool = true;
+//!     pub const MaxBonds: u32 = 10_000;
+//!     pub const ShareTransferCooldown: BlockNumber = 0;
+//!     pub const ExpiredBondTreasury: Option<AccountId> = None;
+//!     pub const MinimumTransfer: Coins = BaseUnit::get() / 1_000;
+//!     pub const KeepAlive: bool = true;
+//!     pub const MaxBatchSize: u32 = 10;
+//! }
+//!
+//! /// The account allowed to submit prices, here the sudo key.
+//! pub struct OracleAccount;
+//! impl Get<AccountId> for OracleAccount {
+//!     fn get() -> AccountId {
+//!         Sudo::key()
+//!     }
 //! }
 //!
 //! impl pallet_stablecoin::Trait for Runtime {
@@ -58,6 +88,28 @@
 //!     type BaseUnit = BaseUnit;
 //!     type InitialSupply = InitialSupply;
 //!     type MinimumSupply = MinimumSupply;
+//!     type ContractionCarryover = ContractionCarryover;
+//!     type MinSeigniorageBalance = MinSeigniorageBalance;
+//!     type MaxLadderSize = MaxLadderSize;
+//!     type SeigniorageFallback = SeigniorageFallback;
+//!     type MaxBidIntakePerBlock = MaxBidIntakePerBlock;
+//!     type MaxShareOfExpansion = MaxShareOfExpansion;
+//!     type ContractionReserve = ContractionReserve;
+//!     type BidCooldown = BidCooldown;
+//!     type BondPayoutMode = BondPayoutMode;
+//!     type MaxCoinSupply = MaxCoinSupply;
+//!     type MinAcceptablePrice = MinAcceptablePrice;
+//!     type MaxAcceptablePrice = MaxAcceptablePrice;
+//!     type BondsExpire = BondsExpire;
+//!     type MaximumBondSize = MaximumBondSize;
+//!     type OracleAccount = OracleAccount;
+//!     type SeigniorageToShareholders = SeigniorageToShareholders;
+//!     type MaxBonds = MaxBonds;
+//!     type ShareTransferCooldown = ShareTransferCooldown;
+//!     type ExpiredBondTreasury = ExpiredBondTreasury;
+//!     type MinimumTransfer = MinimumTransfer;
+//!     type KeepAlive = KeepAlive;
+//!     type MaxBatchSize = MaxBatchSize;
 //! }
 //! ```
 //!
@@ -67,6 +119,16 @@
 //! Stablecoin: pallet_stablecoin::{Module, Call, Storage, Event<T>},
 //! ```
 //!
+//! To keep bids their signer cannot pay for out of the transaction pool, add the
+//! `CheckBidAffordable` signed extension to the `SignedExtra` of your runtime:
+//!
+//! ```rust,ignore
+//! pub type SignedExtra = (
+//!     // ... other extensions
+//!     pallet_stablecoin::CheckBidAffordable<Runtime>,
+//! );
+//! ```
+//!
 //! ### GenesisConfig `chain_spec.rs`
 //!
 //! Runtimes using the pallet need to add the `StablecoinConfig` to their genesis config.
@@ -89,6 +151,8 @@
 //! ```
 //!
 //! With this config the endowed accounts will be the shareholders of the stablecoin.
+//! The `InitialSupply` is handed out to the shareholders according to their shares. There is no
+//! separate founder account, so accounts that are not in the list start without Coins.
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::prelude::*;
@@ -96,30 +160,140 @@ use sp_std::prelude::*;
 use adapters::{BoundedPriorityQueue, BoundedDeque};
 use codec::{Decode, Encode};
 use core::cmp::{max, min, Ord, Ordering};
+use core::convert::TryFrom;
+use core::marker::PhantomData;
 use fixed::{types::extra::U64, FixedU128};
 use frame_support::{
 	debug::native,
 	decl_error, decl_event, decl_module, decl_storage,
 	dispatch::{DispatchError, DispatchResult},
 	ensure,
-	traits::Get,
+	storage::IterableStorageMap,
+	traits::{Get, IsSubType},
+	weights::{DispatchClass, DispatchInfo, FunctionOf, Weight},
 };
 use num_rational::Ratio;
 use orml_traits::BasicCurrency;
 use sp_runtime::{
-	traits::{CheckedMul, Zero},
+	traits::{Bounded, CheckedMul, SignedExtension, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
 	PerThing, Perbill, RuntimeDebug,
 };
 use sp_std::collections::vec_deque::VecDeque;
-use system::ensure_signed;
+use system::{ensure_root, ensure_signed};
+
+pub mod runtime_api;
 
 #[cfg(test)]
 mod tests;
 
 /// Expected price oracle interface. `fetch_price` must return the amount of Coins exchanged for the tracked value.
+///
+/// `fetch_price` must not change any state, it is also used by queries and the runtime API.
+/// Oracles keeping state (e.g., a window of past prices) update it in `record_price`, which the
+/// pallet calls once per block in `on_initialize`.
 pub trait FetchPrice<Balance> {
-	/// Fetch the current price.
+	/// Fetch the current price without changing any state.
 	fn fetch_price() -> Balance;
+
+	/// Record a new price reading. Called once per block before `fetch_price`.
+	fn record_price() {}
+}
+
+/// Adapter for price oracles that report the price of the tracked value with `Decimals` decimals
+/// (e.g., 102_000_000 for 1.02$ with 8 decimals).
+///
+/// Rescales the reported price to the amount of Coins per tracked value based on the `BaseUnit`
+/// of the pallet, rounding to the nearest Coin.
+/// Returns a price of 0 (which is rejected by the pallet) if the rescaled price does not fit into `Coins`.
+pub struct DecimalAdapter<T, Oracle, Decimals>(PhantomData<(T, Oracle, Decimals)>);
+
+impl<T, O, D> FetchPrice<Coins> for DecimalAdapter<T, O, D>
+where
+	T: Trait,
+	O: FetchPrice<u128>,
+	D: Get<u8>,
+{
+	fn record_price() {
+		O::record_price();
+	}
+
+	fn fetch_price() -> Coins {
+		let price = O::fetch_price();
+		let scale = match 10u128.checked_pow(D::get().into()) {
+			Some(scale) => scale,
+			None => {
+				native::error!("too many decimals for price oracle: {}", D::get());
+				return 0;
+			}
+		};
+		price
+			.checked_mul(<Module<T>>::base_unit().into())
+			.and_then(|p| p.checked_add(scale / 2))
+			.map(|p| p / scale)
+			.and_then(|p| Coins::try_from(p).ok())
+			.unwrap_or_else(|| {
+				native::error!("rescaled price overflowed: {}", price);
+				0
+			})
+	}
+}
+
+/// Adapter that smooths the prices reported by `Oracle` by returning the mean of the last
+/// `WindowSize` prices.
+///
+/// `record_price` fetches a new price and stores it in the `PriceWindow`, `fetch_price` only reads
+/// the window. Prices of 0 (which are rejected by the pallet) are not added to the window.
+/// Returns a price of 0 if the window is empty.
+pub struct MovingAverage<Oracle, WindowSize>(PhantomData<(Oracle, WindowSize)>);
+
+impl<O, N> FetchPrice<Coins> for MovingAverage<O, N>
+where
+	O: FetchPrice<Coins>,
+	N: Get<u32>,
+{
+	fn record_price() {
+		O::record_price();
+		let price = O::fetch_price();
+		let window_size = max(1, N::get()) as usize;
+		<PriceWindow>::mutate(|window| {
+			if !price.is_zero() {
+				window.push_back(price);
+			}
+			while window.len() > window_size {
+				window.pop_front();
+			}
+		});
+	}
+
+	fn fetch_price() -> Coins {
+		let window = <PriceWindow>::get();
+		// ignore older prices if the window size was reduced since the last `record_price`
+		let prices = min(window.len(), max(1, N::get()) as usize);
+		if prices == 0 {
+			return 0;
+		}
+		let sum = window
+			.iter()
+			.rev()
+			.take(prices)
+			.fold(0u128, |total, p| total + *p as u128);
+		// the mean of `Coins` values fits into `Coins`
+		(sum / prices as u128) as Coins
+	}
+}
+
+/// Price oracle reading the latest price submitted by the `OracleAccount` via `submit_price`.
+///
+/// Returns a price of 0 (which is rejected by the pallet) until the first price is submitted.
+pub struct StoredPrice<T>(PhantomData<T>);
+
+impl<T: Trait> FetchPrice<Coins> for StoredPrice<T> {
+	fn fetch_price() -> Coins {
+		<Module<T>>::current_price()
+	}
 }
 
 /// The type used to represent the account balance for the stablecoin.
@@ -157,19 +331,134 @@ pub trait Trait: system::Trait {
 	///
 	/// Must be lower than `InitialSupply`.
 	type MinimumSupply: Get<Coins>;
+	/// Whether to carry the part of a contraction that could not be covered by bids over
+	/// to the next contraction (`true`) or to discard it (`false`).
+	type ContractionCarryover: Get<bool>;
+	/// The minimum balance a shareholder needs to hold to receive Coins when the supply is expanded.
+	///
+	/// The Coins of shareholders below the minimum are handed out to the remaining shareholders.
+	type MinSeigniorageBalance: Get<Coins>;
+	/// The maximum amount of bids that can be placed at once with `bid_ladder`.
+	type MaxLadderSize: Get<u32>;
+	/// The account receiving the Coins of an expansion that cannot be handed out because the
+	/// share supply is zero. The Coins are not created (i.e., burned) if this is `None`.
+	type SeigniorageFallback: Get<Option<Self::AccountId>>;
+	/// The maximum amount of Coins that can be payed for bids in a single block.
+	type MaxBidIntakePerBlock: Get<Coins>;
+	/// The maximum fraction of a single expansion handed out to one shareholder.
+	///
+	/// The excess is handed out to the other shareholders.
+	type MaxShareOfExpansion: Get<Perbill>;
+	/// The account whose Coins are burned directly (as a last resort) if there are not enough bids
+	/// to contract the supply. Disabled if `None`.
+	type ContractionReserve: Get<Option<Self::AccountId>>;
+	/// The number of blocks an account needs to wait after placing bids before it can bid again.
+	/// Disabled if zero.
+	type BidCooldown: Get<Self::BlockNumber>;
+	/// How an expansion that does not cover all outstanding bonds is distributed among them.
+	type BondPayoutMode: Get<BondPayoutMode>;
+	/// The maximum amount of Coins in circulation. Expansions are capped at this ceiling.
+	type MaxCoinSupply: Get<Coins>;
+	/// The lowest price reading that is accepted for a rebase. Lower readings are ignored.
+	type MinAcceptablePrice: Get<Coins>;
+	/// The highest price reading that is accepted for a rebase. Higher readings are ignored.
+	type MaxAcceptablePrice: Get<Coins>;
+	/// Whether bonds expire after `ExpirationPeriod`. Bonds are perpetual if this is `false`.
+	type BondsExpire: Get<bool>;
+	/// The maximum payout of a single bond. Larger payouts are split into multiple bonds.
+	type MaximumBondSize: Get<Coins>;
+	/// The account allowed to submit prices with `submit_price` (e.g., a push oracle).
+	type OracleAccount: Get<Self::AccountId>;
+	/// Whether the part of an expansion not covered by bonds is handed out to the shareholders.
+	/// That part is not created at all if this is `false`.
+	type SeigniorageToShareholders: Get<bool>;
+	/// The maximum number of bonds in the bond queue.
+	///
+	/// Bids are no longer filled during a contraction once their bonds would exceed the limit,
+	/// existing bonds are never evicted.
+	///
+	/// Must not exceed `BondIndex::max_value()`, the capacity of the bond queue. Greater values
+	/// are treated as the capacity.
+	type MaxBonds: Get<u32>;
+	/// The number of blocks an account needs to wait after transferring shares before it can
+	/// transfer shares again. Disabled if zero.
+	type ShareTransferCooldown: Get<Self::BlockNumber>;
+	/// The account receiving the payouts of bonds that expire unpaid during an expansion (out of
+	/// the expansion). The payouts are only recorded as `SavedSeigniorage` if `None`, the expansion
+	/// then goes to the next bonds or the shareholders as if the bond had not been there.
+	type ExpiredBondTreasury: Get<Option<Self::AccountId>>;
+	/// The minimum amount of Coins that can be sent with `send_coins`.
+	type MinimumTransfer: Get<Coins>;
+	/// Whether `send_coins` rejects transfers that leave the sender with less than `MinimumTransfer`
+	/// (but more than zero) Coins. The rest of the balance is swept to the receiver if `false`.
+	type KeepAlive: Get<bool>;
+	/// The maximum number of entries in a single batch call, i.e. transfers in `batch_transfer`
+	/// and bids in `bid_ladder`.
+	type MaxBatchSize: Get<u32>;
+}
+
+/// The headline state of the stablecoin, returned by `overview` in a single query.
+///
+/// + `coin_supply` is the amount of Coins in circulation.
+/// + `share_supply` is the total number of shares (saturating at `u64::max_value()`).
+/// + `shareholder_count` is the number of accounts holding shares.
+/// + `bid_count` is the number of bids in the bidding queue.
+/// + `bond_count` is the number of bonds in the bond queue (including expired ones not yet removed).
+/// + `outstanding_bond_liability` is the total payout of the bonds in the bond queue.
+/// + `current_peg_ratio` is the current price and the base unit as `(price, base_unit)`.
+/// + `last_rebase` is the block and the action of the last adjustment that changed the supply.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StablecoinOverview<BlockNumber> {
+	pub coin_supply: Coins,
+	pub share_supply: u64,
+	pub shareholder_count: u32,
+	pub bid_count: u32,
+	pub bond_count: BondIndex,
+	pub outstanding_bond_liability: Coins,
+	pub current_peg_ratio: (Coins, Coins),
+	pub last_rebase: Option<(BlockNumber, SupplyAction)>,
+}
+
+/// The maximum amount of bids allowed in the queue.
+///
+/// Uses the `MaximumBidsOverride` from storage if it is set and falls back to `MaximumBids` otherwise.
+pub struct MaximumBidsLimit<T>(PhantomData<T>);
+
+impl<T: Trait> Get<u64> for MaximumBidsLimit<T> {
+	fn get() -> u64 {
+		<Module<T>>::maximum_bids_override().unwrap_or_else(T::MaximumBids::get)
+	}
 }
 
 /// A bond representing (potential) future payout of Coins.
 ///
-/// Expires at block `expiration` so it will be discarded if payed out after that block.
+/// Expires at block `expiration` so it will be discarded and the Coins payed for it refunded
+/// if payed out after that block.
 ///
 /// + `account` is the recipient of the bond payout.
 /// + `payout` is the amount of Coins payed out.
+/// + `expiration` is the block in which the bond expires.
+/// + `bid_sequence` is the sequence number of the bid the bond was created from.
+/// + `paid` is the amount of Coins payed for the not yet payed out part of the bond.
 #[derive(Encode, Decode, Default, Clone, PartialEq, PartialOrd, Eq, Ord, RuntimeDebug)]
 pub struct Bond<AccountId, BlockNumber> {
 	account: AccountId,
 	payout: Coins,
 	expiration: BlockNumber,
+	bid_sequence: u64,
+	paid: Coins,
+}
+
+impl<AccountId, BlockNumber> Bond<AccountId, BlockNumber> {
+	/// Reduce the payout by `amount` (at most the whole payout) and the Coins payed for the
+	/// bond proportionally.
+	fn reduce_payout(&mut self, amount: Coins) {
+		let amount = min(amount, self.payout);
+		let payout = self.payout - amount;
+		// cannot overflow because the result is at most `paid`
+		self.paid = (self.paid as u128 * payout as u128 / max(1, self.payout) as u128) as Coins;
+		self.payout = payout;
+	}
 }
 
 /// A bid for a bond of the stablecoin at a certain price.
@@ -177,34 +466,89 @@ pub struct Bond<AccountId, BlockNumber> {
 /// + `account` is the bidder.
 /// + `price` is a percentage of 1 coin.
 /// + `quantity` is the amount of Coins gained on payout of the corresponding bond.
+/// + `placed_at` is the block in which the bid was placed.
+/// + `sequence` is a unique number increasing with every bid placed, used to order bids with equal price.
 #[derive(Encode, Decode, Default, Clone, RuntimeDebug)]
-pub struct Bid<AccountId> {
+pub struct Bid<AccountId, BlockNumber> {
 	account: AccountId,
 	price: Perbill,
 	quantity: Coins,
+	placed_at: BlockNumber,
+	sequence: u64,
 }
 
 // Implement `Ord` for `Bid` to get the wanted sorting in the priority queue.
 // TODO: Could this create issues in testing? How to address?
-impl<AccountId> PartialEq for Bid<AccountId> {
+impl<AccountId, BlockNumber> PartialEq for Bid<AccountId, BlockNumber> {
 	fn eq(&self, other: &Self) -> bool {
-		self.price == other.price
+		self.price == other.price && self.sequence == other.sequence
 	}
 }
-impl<AccountId> Eq for Bid<AccountId> {}
+impl<AccountId, BlockNumber> Eq for Bid<AccountId, BlockNumber> {}
 
-impl<AccountId> PartialOrd for Bid<AccountId> {
+impl<AccountId, BlockNumber> PartialOrd for Bid<AccountId, BlockNumber> {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 		Some(self.cmp(other))
 	}
 }
-/// Sort `Bid`s by price.
-impl<AccountId> Ord for Bid<AccountId> {
+/// Sort `Bid`s by price and bids with equal price first come, first served.
+impl<AccountId, BlockNumber> Ord for Bid<AccountId, BlockNumber> {
 	fn cmp(&self, other: &Self) -> Ordering {
-		self.price.cmp(&other.price)
+		// earlier bids (with a lower sequence number) are greater to be popped first
+		self.price
+			.cmp(&other.price)
+			.then_with(|| other.sequence.cmp(&self.sequence))
+	}
+}
+
+/// A change of the coin supply performed by the stablecoin.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum SupplyAction {
+	/// The supply was expanded by the given amount of Coins.
+	Expand(Coins),
+	/// The supply was contracted by the given amount of Coins.
+	Contract(Coins),
+}
+
+// Needed for storing `SupplyAction`s in a storage map.
+impl Default for SupplyAction {
+	fn default() -> Self {
+		SupplyAction::Expand(0)
 	}
 }
 
+/// How an expansion that does not cover all outstanding bonds is distributed among them.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BondPayoutMode {
+	/// Bonds are payed out in the order they were created.
+	Fifo,
+	/// All outstanding bonds are payed out proportionally to their payout.
+	ProRata,
+}
+
+/// The weight of placing a single bid, used to scale the weight of `bid_ladder`.
+pub const BID_WEIGHT: Weight = 10_000;
+
+/// The weight of a single transfer, used to scale the weight of `batch_transfer`.
+pub const TRANSFER_WEIGHT: Weight = 10_000;
+
+/// The weight of fetching the price in `on_initialize` when no supply adjustment happens.
+pub const PRICE_CHECK_WEIGHT: Weight = 10_000;
+
+/// The weight of handling a single bond or bid during a supply adjustment.
+pub const QUEUE_ITEM_WEIGHT: Weight = 10_000;
+
+/// The version of the pallet's storage layout, stored in `StorageVersion`.
+///
+/// Needs to be increased (together with a migration in `on_runtime_upgrade`) whenever the
+/// storage layout changes.
+pub const PALLET_VERSION: u16 = 1;
+
+/// The index type of the rebase history ringbuffer.
+///
+/// Limits the history to the last `u8::max_value()` rebases.
+pub type RebaseIndex = u8;
+
 /// Error returned from `remove_coins` if there is an over- or underflow.
 pub enum BidError {
 	/// `remove_coins` overflowed.
@@ -213,13 +557,21 @@ pub enum BidError {
 	Underflow,
 }
 
-impl<AccountId> Bid<AccountId> {
+impl<AccountId, BlockNumber> Bid<AccountId, BlockNumber> {
 	/// Create a new bid.
-	fn new(account: AccountId, price: Perbill, quantity: Coins) -> Bid<AccountId> {
+	fn new(
+		account: AccountId,
+		price: Perbill,
+		quantity: Coins,
+		placed_at: BlockNumber,
+		sequence: u64,
+	) -> Bid<AccountId, BlockNumber> {
 		Bid {
 			account,
 			price,
 			quantity,
+			placed_at,
+			sequence,
 		}
 	}
 
@@ -231,6 +583,9 @@ impl<AccountId> Bid<AccountId> {
 
 	/// Remove `coins` amount of Coins from the bid, mirroring the changes in quantity
 	/// according to the price attached to the bid.
+	///
+	/// Returns the removed quantity which is rounded down, undercounting the exact quantity by
+	/// less than one unit.
 	fn remove_coins(&mut self, coins: Coins) -> Result<Coins, BidError> {
 		// Inverse price is needed because `self.price` converts from amount of bond payout coins to payment coins,
 		// but we need to convert the other way from payment coins to bond payout coins.
@@ -252,6 +607,14 @@ impl<AccountId> Bid<AccountId> {
 	}
 }
 
+/// The outcome of matching a single bid against a contraction in `match_bids`.
+enum BidMatch<AccountId, BlockNumber> {
+	/// The bid was (partially) filled with `(bid, payment, bond_payout)`, the bid holding its rest.
+	Filled(Bid<AccountId, BlockNumber>, Coins, Coins),
+	/// The bid could not be reduced and needs to be refunded.
+	Unfillable(Bid<AccountId, BlockNumber>),
+}
+
 decl_event!(
 	pub enum Event<T>
 	where
@@ -260,28 +623,68 @@ decl_event!(
 	{
 		/// Successful transfer from the first to the second account.
 		Transfer(AccountId, AccountId, u64),
+		/// Coins were added to the account without a sender (e.g., supply expansion or refund).
+		Deposit(AccountId, Coins),
+		/// Coins were removed from the account without a receiver (e.g., payment for a bid).
+		Withdraw(AccountId, Coins),
 		/// New bid was registered for the account at given price and amount.
 		NewBid(AccountId, Perbill, u64),
 		/// A bid was refunded (repayed and removed from the queue).
 		RefundedBid(AccountId, u64),
-		/// A new bond was created for the account with payout and expiration.
-		NewBond(AccountId, u64, BlockNumber),
-		/// A bond was payed out to the account.
+		/// A bid of the account could only be refunded partially because the bid escrow was short.
+		/// Contains the expected and the actually refunded amount.
+		PartialRefund(AccountId, Coins, Coins),
+		/// A new bond was created for the account with payout, expiration and the sequence number of
+		/// the bid it was created from.
+		NewBond(AccountId, u64, BlockNumber, u64),
+		/// The bid with the given sequence number of the account was (partially) filled for the
+		/// given amount of Coins.
+		BidFilled(AccountId, u64, Coins),
+		/// A bond was fully payed out to the account with the given payout and removed from the
+		/// bond queue.
 		BondFulfilled(AccountId, u64),
-		/// A bond was partially payed out to the account.
+		/// A bond was partially payed out to the account. Contains the payout remaining in the queue.
 		BondPartiallyFulfilled(AccountId, u64),
-		/// A bond expired and was removed from the bond queue.
-		BondExpired(AccountId, u64),
+		/// A bond expired and was removed from the bond queue, refunding the given amount of Coins
+		/// payed for it to the account.
+		BondExpired(AccountId, Coins),
 		/// All bids at and above the given price were cancelled for the account.
 		CancelledBidsAbove(AccountId, Perbill),
 		/// All bids at and below the given price were cancelled for the account.
 		CancelledBidsBelow(AccountId, Perbill),
 		/// All bids were cancelled for the account.
 		CancelledBids(AccountId),
+		/// The bids of the account at a price were cancelled, refunding the given amount of Coins.
+		BidCancelled(AccountId, Coins),
 		/// The supply was expanded by the amount.
 		ExpandedSupply(u64),
 		/// The supply was contracted by the amount.
 		ContractedSupply(u64),
+		/// The maximum amount of bids in the queue was changed to the given value.
+		NewMaximumBids(u64),
+		/// The stablecoin was re-pegged to the given base unit.
+		Repegged(Coins),
+		/// The given number of expired bonds were removed from the bond queue.
+		BondsCompacted(u32),
+		/// The account gave up the given amount of shares.
+		SharesRenounced(AccountId, u64),
+		/// The given amount of shares was transferred from the first to the second account.
+		SharesTransferred(AccountId, AccountId, u64),
+		/// The price band considered on target was changed to the given lower and upper end.
+		NewTargetBand(Coins, Coins),
+		/// The supply was contracted by burning the amount from the `ContractionReserve`.
+		DirectContraction(Coins),
+		/// The price reading was outside of the acceptable price range, the rebase was skipped.
+		PriceOutOfBounds(Coins),
+		/// The given amount of Coins was handed out to the shareholders.
+		SeigniorageDistributed(Coins),
+		/// The oracle submitted the given price.
+		PriceSubmitted(Coins),
+		/// The given amount of Coins was payed out to the account from a (fully or partially)
+		/// fulfilled bond.
+		BondReleased(AccountId, Coins),
+		/// A bond of the account with the given payout expired unpaid during an expansion.
+		BondExpiredUnpaid(AccountId, Coins),
 	}
 );
 
@@ -308,6 +711,46 @@ decl_error! {
 		BondPriceTooLow,
 		/// The bond being bid for is not big enough (in amount of Coins).
 		BondQuantityTooLow,
+		/// The shares sum up to zero so there is nobody to hand out Coins to.
+		ZeroShareSupply,
+		/// The base unit needs to be greater than zero.
+		ZeroBaseUnit,
+		/// The bid ladder contains more than `MaxLadderSize` bids.
+		LadderTooLarge,
+		/// The bid would exceed the maximum bid intake of this block.
+		BidIntakeExceeded,
+		/// The account does not hold enough shares.
+		InsufficientShares,
+		/// The target band needs to be non-zero with the lower end not above the upper end.
+		InvalidTargetBand,
+		/// The account placed bids less than `BidCooldown` blocks ago.
+		BidOnCooldown,
+		/// Bidding is disabled because the maximum amount of bids is zero.
+		BiddingDisabled,
+		/// The sum of all shares is greater than `u64::max_value()`.
+		ShareSupplyOverflow,
+		/// The coin supply would exceed `MaxCoinSupply`.
+		SupplyCeilingReached,
+		/// The account has no bid at the given price.
+		NoMatchingBid,
+		/// Only the `OracleAccount` can submit prices.
+		NotOracle,
+		/// The payment for the bid is greater than the accepted maximum cost.
+		SlippageExceeded,
+		/// The supply was already adjusted in this block.
+		AlreadyRebased,
+		/// The account transferred shares less than `ShareTransferCooldown` blocks ago.
+		ShareTransferOnCooldown,
+		/// The transfer is smaller than `MinimumTransfer`.
+		BelowMinimumTransfer,
+		/// The transfer would leave the sender with less than `MinimumTransfer` Coins.
+		WouldLeaveDust,
+		/// The batch contains more than `MaxBatchSize` entries.
+		BatchTooLarge,
+		/// The amount needs to be greater than zero.
+		ZeroAmount,
+		/// The bid ladder contains no bids.
+		EmptyLadder,
 	}
 }
 
@@ -335,6 +778,26 @@ decl_storage! {
 
 		/// The total amount of Coins in circulation.
 		CoinSupply get(fn coin_supply): Coins = 0;
+		/// The total amount of Coins handed out to the shareholders in supply expansions.
+		CumulativeSeigniorage get(fn cumulative_seigniorage): Coins = 0;
+		/// The total payout of bonds that expired unpaid during expansions without being routed to
+		/// the `ExpiredBondTreasury`. Only a record: the expansions are not reduced by it.
+		SavedSeigniorage get(fn saved_seigniorage): Coins = 0;
+		/// The block and the action of the last adjustment that actually changed the coin supply.
+		LastRebase get(fn last_rebase): Option<(T::BlockNumber, SupplyAction)>;
+		/// The last block `on_initialize` ran a supply adjustment for. Guards against rebasing twice.
+		LastRebaseBlock get(fn last_rebase_block): Option<T::BlockNumber>;
+		/// The latest price submitted by the `OracleAccount`, read by `StoredPrice`.
+		CurrentPrice get(fn current_price): Coins;
+		/// The last prices fetched by the `MovingAverage` adapter, oldest first.
+		PriceWindow get(fn price_window): VecDeque<Coins>;
+		/// The history of adjustments that changed the coin supply.
+		RebaseHistory get(fn get_rebase):
+			map hasher(twox_64_concat) RebaseIndex => (T::BlockNumber, SupplyAction);
+		/// Start index and length used to implement a ringbuffer on top of the `RebaseHistory` map.
+		RebaseHistoryRange get(fn rebase_history_range): (RebaseIndex, RebaseIndex) = (0, 0);
+		/// The amount of Coins payed for bids in the given block (used for throttling bids).
+		BidIntake get(fn bid_intake): (T::BlockNumber, Coins);
 
 		/// The available bonds for contracting supply.
 		Bonds get(fn get_bond): map hasher(twox_64_concat) BondIndex => Bond<T::AccountId, T::BlockNumber>;
@@ -342,10 +805,43 @@ decl_storage! {
 		BondsRange get(fn bonds_range): (BondIndex, BondIndex) = (0, 0);
 
 		/// The current bidding queue for bonds.
-		BondBids get(fn bond_bids): Vec<Bid<T::AccountId>>;
+		BondBids get(fn bond_bids): Vec<Bid<T::AccountId, T::BlockNumber>>;
+		/// The sequence number of the next bid.
+		BidSequence get(fn bid_sequence): u64;
+		/// The amount of Coins payed for the bids in the queue, available for refunds.
+		BidEscrow get(fn bid_escrow): Coins;
+		/// The block in which the account last placed bids.
+		LastBidAt get(fn last_bid_at): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		/// The block in which the account last transferred shares.
+		LastShareTransferAt get(fn last_share_transfer_at):
+			map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		/// The price at which bond payouts of the account are bid for new bonds instead of being
+		/// credited to the balance.
+		AutoRebid get(fn auto_rebid): map hasher(blake2_128_concat) T::AccountId => Option<Perbill>;
+		/// Overrides the configured `MaximumBids` if set.
+		MaximumBidsOverride get(fn maximum_bids_override): Option<u64>;
+		/// Overrides the configured `BaseUnit` if set.
+		BaseUnitOverride get(fn base_unit_override): Option<Coins>;
+		/// The lower end of the price band that is considered on target (defaults to the base unit).
+		TargetBandLow get(fn target_band_low): Option<Coins>;
+		/// The upper end of the price band that is considered on target (defaults to the base unit).
+		TargetBandHigh get(fn target_band_high): Option<Coins>;
+
+		/// The amount of Coins that could not be contracted because of a lack of bids.
+		///
+		/// Only accumulates if `ContractionCarryover` is enabled and never exceeds the amount of Coins
+		/// above `MinimumSupply`.
+		PendingContraction get(fn pending_contraction): Coins;
+
+		/// The version of the storage layout, see `PALLET_VERSION`.
+		///
+		/// Chains initialized before the version was introduced read as version 0.
+		StorageVersion get(fn storage_version): u16;
 	}
 	add_extra_genesis {
 		/// The shareholders to initialize the stablecoin with.
+		///
+		/// The initial supply is handed out to them, nobody else receives Coins at genesis.
 		config(shareholders):
 			Vec<(T::AccountId, u64)>;
 		build(|config: &GenesisConfig<T>| {
@@ -353,16 +849,38 @@ decl_storage! {
 				T::MinimumSupply::get() < T::InitialSupply::get(),
 				"initial coin supply needs to be greater than the minimum"
 			);
+			// Mirrors the `ZeroBaseUnit` check of `repeg`, prices are measured against the base unit.
+			assert!(!T::BaseUnit::get().is_zero(), "base unit needs to be greater than zero");
 
+			// The stablecoin is only initialized once, there is no initialization after genesis.
+			assert!(<Module<T>>::coin_supply().is_zero(), "stablecoin is already initialized");
 			assert!(!config.shareholders.is_empty(), "need at least one shareholder");
+			// Otherwise the initial handout (and every later one) would fail with `ZeroShareSupply`.
+			assert!(
+				config.shareholders.iter().any(|(_acc, shares)| *shares > 0),
+				"need at least one shareholder with a share"
+			);
+			// The bond queue would write over its oldest bonds beyond its capacity.
+			assert!(
+				T::MaxBonds::get() <= BondIndex::max_value() as u32,
+				"max bonds need to fit into the bond queue"
+			);
 			// TODO: make sure shareholders are unique?
 
 			// Hand out the initial coin supply to the shareholders.
 			<Module<T>>::hand_out_coins(&config.shareholders, T::InitialSupply::get(), <Module<T>>::coin_supply())
 				.expect("initialization handout should not fail");
+			// The handout updates the supply so it must not be set separately.
+			assert_eq!(
+				<Module<T>>::coin_supply(),
+				T::InitialSupply::get(),
+				"coin supply should equal the initial supply after initialization"
+			);
 
 			// Store the shareholders with their shares.
 			<Shares<T>>::put(&config.shareholders);
+
+			<StorageVersion>::put(PALLET_VERSION);
 		});
 	}
 }
@@ -382,28 +900,163 @@ decl_module! {
 		const AdjustmentFrequency: T::BlockNumber = T::AdjustmentFrequency::get();
 		/// The minimum amount of Coins that will be in circulation.
 		const MinimumSupply: Coins = T::MinimumSupply::get();
+		/// Whether uncovered contractions are carried over to the next contraction.
+		const ContractionCarryover: bool = T::ContractionCarryover::get();
+		/// The minimum balance of a shareholder to receive Coins on supply expansion.
+		const MinSeigniorageBalance: Coins = T::MinSeigniorageBalance::get();
+		/// The maximum amount of bids in a bid ladder.
+		const MaxLadderSize: u32 = T::MaxLadderSize::get();
+		/// The maximum amount of Coins payed for bids per block.
+		const MaxBidIntakePerBlock: Coins = T::MaxBidIntakePerBlock::get();
+		/// The maximum fraction of an expansion handed out to one shareholder.
+		const MaxShareOfExpansion: Perbill = T::MaxShareOfExpansion::get();
+		/// The number of blocks an account needs to wait between placing bids.
+		const BidCooldown: T::BlockNumber = T::BidCooldown::get();
+		/// How an expansion that does not cover all outstanding bonds is distributed among them.
+		const BondPayoutMode: BondPayoutMode = T::BondPayoutMode::get();
+		/// The maximum amount of Coins in circulation.
+		const MaxCoinSupply: Coins = T::MaxCoinSupply::get();
+		/// The lowest price reading that is accepted for a rebase.
+		const MinAcceptablePrice: Coins = T::MinAcceptablePrice::get();
+		/// The highest price reading that is accepted for a rebase.
+		const MaxAcceptablePrice: Coins = T::MaxAcceptablePrice::get();
+		/// Whether bonds expire after `ExpirationPeriod`.
+		const BondsExpire: bool = T::BondsExpire::get();
+		/// The maximum payout of a single bond.
+		const MaximumBondSize: Coins = T::MaximumBondSize::get();
+		/// The account allowed to submit prices.
+		const OracleAccount: T::AccountId = T::OracleAccount::get();
+		/// Whether expansions not covered by bonds are handed out to the shareholders.
+		const SeigniorageToShareholders: bool = T::SeigniorageToShareholders::get();
+		/// The maximum number of bonds in the bond queue.
+		const MaxBonds: u32 = T::MaxBonds::get();
+		/// The number of blocks an account needs to wait between share transfers.
+		const ShareTransferCooldown: T::BlockNumber = T::ShareTransferCooldown::get();
+		/// The account receiving the payouts of bonds that expire unpaid during an expansion.
+		const ExpiredBondTreasury: Option<T::AccountId> = T::ExpiredBondTreasury::get();
+		/// The minimum amount of Coins that can be sent.
+		const MinimumTransfer: Coins = T::MinimumTransfer::get();
+		/// Whether transfers leaving the sender with less than `MinimumTransfer` Coins are rejected.
+		const KeepAlive: bool = T::KeepAlive::get();
+		/// The maximum number of entries in a batch call.
+		const MaxBatchSize: u32 = T::MaxBatchSize::get();
 
 		fn deposit_event() = default;
 
 		/// Transfer `amount` Coins from the sender to the account `to`.
 		///
+		/// Fails if `amount` is smaller than `MinimumTransfer`. If the transfer would leave the sender
+		/// with less than `MinimumTransfer` (but more than zero) Coins it fails if `KeepAlive` is
+		/// set and sends the whole balance of the sender otherwise. The `Transfer` event carries the
+		/// amount actually sent.
+		///
+		/// Transfers to the sender itself only check the balance and emit no `Transfer` event.
+		///
 		/// **Weight:**
 		/// - complexity: `O(1)`
 		/// - DB access: 2 storage map reads + 2 storage map writes
 		pub fn send_coins(origin, to: T::AccountId, amount: u64) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			let amount = Self::amount_without_dust(&sender, amount)?;
+			Self::transfer_from_to(&sender, &to, amount)?;
+			if sender != to {
+				Self::deposit_event(RawEvent::Transfer(sender, to, amount));
+			}
+			Ok(())
+		}
+
+		/// Transfer the whole balance of the sender to the account `to`.
+		///
+		/// Fails with `InsufficientBalance` if the sender has no Coins. Not limited by
+		/// `MinimumTransfer` as it leaves no dust behind. Transfers to the sender itself emit no
+		/// `Transfer` event.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 3 storage map reads + 2 storage map writes
+		pub fn transfer_all(origin, to: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let amount = Self::get_balance(&sender);
+			ensure!(amount > 0, Error::<T>::InsufficientBalance);
 			Self::transfer_from_to(&sender, &to, amount)?;
-			Self::deposit_event(RawEvent::Transfer(sender, to, amount));
+			if sender != to {
+				Self::deposit_event(RawEvent::Transfer(sender, to, amount));
+			}
+			Ok(())
+		}
+
+		/// Transfer Coins from the sender to multiple accounts at once.
+		///
+		/// Each transfer is given as `(to, amount)` and needs to be at least `MinimumTransfer`.
+		/// Either all transfers are made or none if the sender cannot pay for all of them. Fails
+		/// with `WouldLeaveDust` if the batch would leave the sender with less than
+		/// `MinimumTransfer` (but more than zero) Coins, regardless of `KeepAlive`. Transfers to
+		/// the sender itself emit no `Transfer` event.
+		///
+		/// **Weight:**
+		/// - complexity: `O(T)`
+		///   - `T` being the number of transfers in the batch, limited to `MaxBatchSize`
+		/// - DB access: `T + 1` storage map reads + `2 * T` storage map writes
+		/// - declared weight: `TRANSFER_WEIGHT` per transfer in the batch on top of the base weight
+		#[weight = FunctionOf(
+			|args: (&Vec<(T::AccountId, Coins)>,)| {
+				TRANSFER_WEIGHT.saturating_mul(args.0.len() as Weight).saturating_add(TRANSFER_WEIGHT)
+			},
+			DispatchClass::Normal,
+			true
+		)]
+		pub fn batch_transfer(origin, transfers: Vec<(T::AccountId, Coins)>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(transfers.len() as u64 <= T::MaxBatchSize::get().into(), Error::<T>::BatchTooLarge);
+			let minimum = T::MinimumTransfer::get();
+			let mut total: Coins = 0;
+			for (_, amount) in transfers.iter() {
+				ensure!(*amount >= minimum, Error::<T>::BelowMinimumTransfer);
+				total = total.checked_add(*amount).ok_or(Error::<T>::InsufficientBalance)?;
+			}
+			let left = Self::get_balance(&sender)
+				.checked_sub(total)
+				.ok_or(Error::<T>::InsufficientBalance)?;
+			ensure!(left == 0 || left >= minimum, Error::<T>::WouldLeaveDust);
+
+			// ↑ verify ↑
+			// ↓ update ↓
+			for (to, amount) in transfers {
+				// Cannot fail: the sender holds the total and no balance can overflow as the sum of
+				// all balances is the coin supply.
+				Self::transfer_from_to(&sender, &to, amount)?;
+				if sender != to {
+					Self::deposit_event(RawEvent::Transfer(sender.clone(), to, amount));
+				}
+			}
+			Ok(())
+		}
+
+		/// Transfer `amount` Coins from the account `from` to the account `to`.
+		///
+		/// Can only be called by root (e.g., to recover funds from a compromised account).
+		/// Transfers from an account to itself emit no `Transfer` event.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 2 storage map reads + 2 storage map writes
+		pub fn force_transfer(origin, from: T::AccountId, to: T::AccountId, amount: u64) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::transfer_from_to(&from, &to, amount)?;
+			if from != to {
+				Self::deposit_event(RawEvent::Transfer(from, to, amount));
+			}
 			Ok(())
 		}
 
-		/// Bid for `quantity` Coins at a `price`.
+		/// Bid for `quantity` Coins at a `price`, paying at most `max_cost` Coins.
 		///
 		/// + `price` is a fraction of the desired payout quantity (e.g., 80%).
 		/// + Expects a `quantity` of a least `BaseUnit`.
+		/// + Returns `SlippageExceeded` if the rounded payment is greater than `max_cost`.
 		///
-		/// Example: `bid_for_bond(origin, Perbill::from_percent(80), 5 * BaseUnit)` will bid
-		/// for a bond with a payout of `5 * BaseUnit` Coins for a price of
+		/// Example: `bid_for_bond(origin, Perbill::from_percent(80), 5 * BaseUnit, 4 * BaseUnit)`
+		/// will bid for a bond with a payout of `5 * BaseUnit` Coins for a price of
 		/// `0.8 * 5 * BaseUnit = 4 * BaseUnit` Coins.
 		///
 		/// **Weight:**
@@ -411,26 +1064,95 @@ decl_module! {
 		///   - `B` being the number of bids in the bidding auction, limited to `MaximumBids`
 		/// - DB access:
 		///   - read and write bids from and to DB
+		///   - 1 read and 1 write for the bid intake
 		///   - 1 DB storage map write to pay the bid
 		///   - 1 potential DB storage map write to refund evicted bid
-		pub fn bid_for_bond(origin, price: Perbill, quantity: Coins) -> DispatchResult {
+		pub fn bid_for_bond(origin, price: Perbill, quantity: Coins, max_cost: Coins) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_bidding_enabled()?;
+			Self::ensure_valid_bid(price, quantity)?;
+			Self::ensure_bid_cooldown(&who)?;
 
-			ensure!(price <= Perbill::from_percent(100), Error::<T>::BondPriceOver100Percent);
-			ensure!(price > T::MinimumBondPrice::get(), Error::<T>::BondPriceTooLow);
-			ensure!(quantity >= T::BaseUnit::get(), Error::<T>::BondQuantityTooLow);
-
-			let bid = Bid::new(who.clone(), price, quantity);
+			let payment = price * quantity;
+			ensure!(payment <= max_cost, Error::<T>::SlippageExceeded);
+			let intake = Self::checked_bid_intake(payment)?;
 
 			// ↑ verify ↑
-			Self::remove_balance(&who, bid.payment())?;
+			Self::remove_balance(&who, payment)?;
 			// ↓ update ↓
-			Self::add_bid(bid);
+			Self::update_bid_intake(intake);
+			<LastBidAt<T>>::insert(&who, <system::Module<T>>::block_number());
+			Self::add_bid(Self::new_bid(who.clone(), price, quantity));
 			Self::deposit_event(RawEvent::NewBid(who, price, quantity));
 
 			Ok(())
 		}
 
+		/// Bid for `quantity` Coins at a `price` without limiting the payment.
+		///
+		/// Same as `bid_for_bond` with a `max_cost` of `Coins::max_value()`.
+		///
+		/// **Weight:** see `bid_for_bond`
+		pub fn bid_for_bond_simple(origin, price: Perbill, quantity: Coins) -> DispatchResult {
+			Self::bid_for_bond(origin, price, quantity, Coins::max_value())
+		}
+
+		/// Place multiple bids (e.g., at descending prices) at once.
+		///
+		/// Each bid is given as `(price, quantity)` and validated like in `bid_for_bond`.
+		/// Either all bids are placed or none if one of them is invalid or the sender cannot
+		/// pay for all of them. Fails with `EmptyLadder` if there are no bids.
+		///
+		/// **Weight:**
+		/// - complexity: `O(L * B)`
+		///   - `L` being the number of bids in the ladder, limited to `MaxLadderSize` and `MaxBatchSize`
+		///   - `B` being the number of bids in the bidding auction, limited to `MaximumBids`
+		/// - DB access:
+		///   - 1 DB storage map write to pay for the bids
+		///   - 1 read and 1 write for the bid intake
+		///   - read and write bids from and to DB
+		///   - `L` potential DB storage map writes to refund evicted bids
+		/// - declared weight: `BID_WEIGHT` per bid in the ladder on top of the base weight
+		#[weight = FunctionOf(
+			|args: (&Vec<(Perbill, Coins)>,)| {
+				BID_WEIGHT.saturating_mul(args.0.len() as Weight).saturating_add(BID_WEIGHT)
+			},
+			DispatchClass::Normal,
+			true
+		)]
+		pub fn bid_ladder(origin, bids: Vec<(Perbill, Coins)>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!bids.is_empty(), Error::<T>::EmptyLadder);
+			ensure!(bids.len() as u64 <= T::MaxLadderSize::get().into(), Error::<T>::LadderTooLarge);
+			ensure!(bids.len() as u64 <= T::MaxBatchSize::get().into(), Error::<T>::BatchTooLarge);
+			Self::ensure_bidding_enabled()?;
+			Self::ensure_bid_cooldown(&who)?;
+			let mut total: Coins = 0;
+			for (price, quantity) in bids.iter() {
+				Self::ensure_valid_bid(*price, *quantity)?;
+				total = total
+					.checked_add(*price * *quantity)
+					.ok_or(Error::<T>::InsufficientBalance)?;
+			}
+			let intake = Self::checked_bid_intake(total)?;
+
+			// ↑ verify ↑
+			Self::remove_balance(&who, total)?;
+			// ↓ update ↓
+			Self::update_bid_intake(intake);
+			<LastBidAt<T>>::insert(&who, <system::Module<T>>::block_number());
+			<BidEscrow>::mutate(|escrow| *escrow = escrow.saturating_add(total));
+			let mut queue = Self::bids_transient();
+			for (price, quantity) in bids {
+				queue
+					.push(Self::new_bid(who.clone(), price, quantity))
+					.map(|to_refund| Self::refund_bid(&to_refund));
+				Self::deposit_event(RawEvent::NewBid(who.clone(), price, quantity));
+			}
+
+			Ok(())
+		}
+
 		/// Cancel all bids at or below `price` of the sender and refund the Coins.
 		///
 		/// **Weight:**
@@ -473,95 +1195,407 @@ decl_module! {
 			Ok(())
 		}
 
-		/// Adjust the amount of Coins according to the price.
+		/// Cancel all bids of the sender at exactly `price` and refund the Coins.
+		///
+		/// Partially filled bids only refund the Coins still payed for their remaining quantity.
+		/// Returns `NoMatchingBid` if the sender has no bid at `price`.
 		///
 		/// **Weight:**
-		/// - complexity: `O(F + P)`
-		///   - `F` being the complexity of `CoinPrice::fetch_price()`
-		///   - `P` being the complexity of `on_block_with_price`
-		fn on_initialize(n: T::BlockNumber) {
-			let price = T::CoinPrice::fetch_price();
-			Self::on_block_with_price(n, price).unwrap_or_else(|e| {
-				native::error!("could not adjust supply: {:?}", e);
-			});
-		}
-	}
-}
+		/// - complexity: `O(B)`
+		///   - `B` being the number of bids in the bidding auction, limited to `MaximumBids`
+		/// - DB access: read and write bids from and to DB
+		pub fn cancel_bid(origin, price: Perbill) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let matches = |bid: &Bid<T::AccountId, T::BlockNumber>| bid.account == who && bid.price == price;
+			ensure!(Self::bond_bids().iter().any(matches), Error::<T>::NoMatchingBid);
+			// ↑ verify ↑
+			// ↓ update ↓
+			let refunded = Self::cancel_bids(matches);
+			Self::deposit_event(RawEvent::BidCancelled(who, refunded));
 
-// Implement the BasicCurrency to allow other pallets to interact programmatically
-// with the Stablecoin.
-impl<T: Trait> BasicCurrency<T::AccountId> for Module<T> {
-	type Balance = Coins;
+			Ok(())
+		}
 
-	/// Return the amount of Coins in circulation.
-	///
-	/// **Weight:**
-	/// - complexity: `O(1)`
-	/// - DB access: 1 read
-	fn total_issuance() -> Self::Balance {
-		Self::coin_supply()
-	}
+		/// Enable (by passing a price) or disable (by passing `None`) automatically bidding bond
+		/// payouts of the sender at `price` instead of crediting them to the balance.
+		///
+		/// Payouts too small for a valid bid are credited to the balance, as are payouts whose bid
+		/// would violate the `BidCooldown` or exceed the `MaxBidIntakePerBlock`.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 1 write
+		pub fn set_auto_rebid(origin, price: Option<Perbill>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if let Some(price) = price {
+				Self::ensure_valid_bid(price, Self::base_unit())?;
+			}
+			// ↑ verify ↑
+			// ↓ update ↓
+			<AutoRebid<T>>::set(&who, price);
 
-	/// Return the balance of the given account.
-	///
-	/// **Weight:**
-	/// - complexity: `O(1)`
-	/// - DB access: 1 read from balance storage map
-	fn total_balance(who: &T::AccountId) -> Self::Balance {
-		Self::get_balance(who)
-	}
+			Ok(())
+		}
 
-	/// Return the free balance of the given account.
-	///
-	/// Equal to `total_balance` for this stablecoin.
-	///
-	/// **Weight:**
-	/// - complexity: `O(1)`
-	/// - DB access: 1 read from balance storage map
-	fn free_balance(who: &T::AccountId) -> Self::Balance {
-		Self::get_balance(who)
-	}
+		/// Permanently give up `amount` shares of the sender.
+		///
+		/// The remaining shareholders receive proportionally more Coins on future handouts.
+		/// The sender is removed from the shareholders if no shares are left. Fails with
+		/// `ZeroAmount` if `amount` is zero.
+		///
+		/// **Weight:**
+		/// - complexity: `O(S)` with `S` being the number of shareholders
+		/// - DB access: 1 read and 1 write for shares
+		pub fn renounce_shares(origin, amount: u64) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(amount > 0, Error::<T>::ZeroAmount);
+			let mut shares = Self::shares();
+			let index = shares
+				.iter()
+				.position(|(acc, _s)| acc == &who)
+				.ok_or(Error::<T>::InsufficientShares)?;
+			let remaining = shares[index]
+				.1
+				.checked_sub(amount)
+				.ok_or(Error::<T>::InsufficientShares)?;
+			// ↑ verify ↑
+			// ↓ update ↓
+			if remaining.is_zero() {
+				shares.remove(index);
+			} else {
+				shares[index].1 = remaining;
+			}
+			<Shares<T>>::put(shares);
+			Self::deposit_event(RawEvent::SharesRenounced(who, amount));
 
-	/// Cannot withdraw from stablecoin accounts. Returns `Ok(())` if `amount` is 0, otherwise returns an error.
-	fn ensure_can_withdraw(_who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
-		if amount.is_zero() {
-			return Ok(());
+			Ok(())
 		}
-		Err(DispatchError::Other("cannot change issuance for stablecoins"))
-	}
 
-	/// Transfer `amount` from one account to another.
-	///
-	/// **Weight:**
-	/// - complexity: `O(1)`
-	/// - DB access: 2 reads and write from and to balance storage map
-	fn transfer(from: &T::AccountId, to: &T::AccountId, amount: Self::Balance) -> DispatchResult {
-		Self::transfer_from_to(from, to, amount)
-	}
+		/// Transfer `amount` shares from the sender to `to`, keeping the share supply constant.
+		///
+		/// The sender is removed from the shareholders if no shares are left and `to` is added
+		/// if not yet a shareholder. Transfers to the sender only check the shares. Fails with
+		/// `ZeroAmount` if `amount` is zero, so no shareholders without shares are added.
+		///
+		/// The sender needs to wait `ShareTransferCooldown` blocks between transfers.
+		///
+		/// **Weight:**
+		/// - complexity: `O(S)` with `S` being the number of shareholders
+		/// - DB access:
+		///   - 1 read and 1 write for shares
+		///   - 1 read and 1 write for the last transfer of the sender
+		pub fn transfer_shares(origin, to: T::AccountId, amount: u64) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(amount > 0, Error::<T>::ZeroAmount);
+			Self::ensure_share_transfer_cooldown(&who)?;
+			let mut shares = Self::shares();
+			let index = shares
+				.iter()
+				.position(|(acc, _s)| acc == &who)
+				.ok_or(Error::<T>::InsufficientShares)?;
+			let remaining = shares[index]
+				.1
+				.checked_sub(amount)
+				.ok_or(Error::<T>::InsufficientShares)?;
+			if who == to {
+				return Ok(());
+			}
+			// ↑ verify ↑
+			// ↓ update ↓
+			if remaining.is_zero() {
+				shares.remove(index);
+			} else {
+				shares[index].1 = remaining;
+			}
+			match shares.iter_mut().find(|(acc, _s)| acc == &to) {
+				// cannot overflow because the share supply fits into `u64`
+				Some((_acc, to_shares)) => *to_shares += amount,
+				None => shares.push((to.clone(), amount)),
+			}
+			<Shares<T>>::put(shares);
+			<LastShareTransferAt<T>>::insert(&who, <system::Module<T>>::block_number());
+			Self::deposit_event(RawEvent::SharesTransferred(who, to, amount));
 
-	/// Noop that returns an error. Cannot change the issuance of a stablecoin.
-	fn deposit(_who: &T::AccountId, _amount: Self::Balance) -> DispatchResult {
-		Err(DispatchError::Other("cannot change issuance for stablecoins"))
-	}
+			Ok(())
+		}
 
-	/// Noop that returns an error. Cannot change the issuance of a stablecoin.
-	fn withdraw(_who: &T::AccountId, _amount: Self::Balance) -> DispatchResult {
-		Err(DispatchError::Other("cannot change issuance for stablecoins"))
-	}
+		/// Submit the current `price` (amount of Coins per tracked value) for `StoredPrice`.
+		///
+		/// Can only be called by the `OracleAccount`. Later submissions overwrite earlier ones,
+		/// so only the last price submitted before a rebase is used.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 1 write
+		pub fn submit_price(origin, price: Coins) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(who == T::OracleAccount::get(), Error::<T>::NotOracle);
+			// ↑ verify ↑
+			// ↓ update ↓
+			<CurrentPrice>::put(price);
+			Self::deposit_event(RawEvent::PriceSubmitted(price));
 
-	/// Test whether the given account can be slashed with `value`.
-	///
-	/// **Weight:**
-	/// - complexity: `O(1)`
-	/// - DB access: 1 read from balance storage map
-	fn can_slash(who: &T::AccountId, value: Self::Balance) -> bool {
-		if value.is_zero() {
-			return true;
+			Ok(())
 		}
-		Self::get_balance(who) >= value
-	}
 
-	/// Slash account `who` by `amount` returning the actual amount slashed.
+		/// Override the maximum amount of bids in the bidding queue or reset it to
+		/// the configured `MaximumBids` by passing `None`.
+		///
+		/// Bids that do not fit into the queue anymore are removed starting with the
+		/// lowest bids and the Coins are refunded to the bidders.
+		///
+		/// Can only be called by root.
+		///
+		/// **Weight:**
+		/// - complexity: `O(B)`
+		///   - `B` being the number of bids in the bidding auction, limited to `MaximumBids`
+		/// - DB access:
+		///   - 1 write for the override
+		///   - read and write bids from and to DB
+		///   - refund up to `B` bids
+		pub fn set_maximum_bids(origin, maximum: Option<u64>) -> DispatchResult {
+			ensure_root(origin)?;
+			// ↑ verify ↑
+			// ↓ update ↓
+			<MaximumBidsOverride>::set(maximum);
+			let maximum = MaximumBidsLimit::<T>::get();
+			Self::truncate_bids(maximum);
+			Self::deposit_event(RawEvent::NewMaximumBids(maximum));
+
+			Ok(())
+		}
+
+		/// Remove up to `limit` expired bonds from the front of the bond queue.
+		///
+		/// Expired bonds are otherwise only removed once they are reached during a supply expansion.
+		/// The Coins payed for the removed bonds are refunded as long as the supply stays within
+		/// `MaxCoinSupply`, expired bonds exceeding it are kept.
+		///
+		/// **Weight:**
+		/// - complexity: `O(L)` with `L` being `limit`
+		/// - DB access:
+		///   - read and write bonds queue bounds
+		///   - up to `L + 1` bond reads and removals and up to `L + 1` bond writes
+		///   - up to `L` balance writes + read and write coin supply
+		pub fn compact_bonds(origin, limit: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+			// ↑ verify ↑
+			// ↓ update ↓
+			let removed = Self::remove_expired_bonds(limit);
+			Self::deposit_event(RawEvent::BondsCompacted(removed));
+
+			Ok(())
+		}
+
+		/// Re-peg the stablecoin by changing the amount of Coins that are meant to track the value.
+		///
+		/// Existing balances, bids and bonds keep their nominal value in Coins, but all future
+		/// supply adjustments target `new_base_unit` Coins per tracked value.
+		/// Note: The price oracle needs to report prices for the (new) tracked value.
+		///
+		/// Can only be called by root.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 1 write
+		pub fn repeg(origin, new_base_unit: Coins) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!new_base_unit.is_zero(), Error::<T>::ZeroBaseUnit);
+			// ↑ verify ↑
+			// ↓ update ↓
+			<BaseUnitOverride>::put(new_base_unit);
+			Self::deposit_event(RawEvent::Repegged(new_base_unit));
+
+			Ok(())
+		}
+
+		/// Set the band of prices `(low, high)` that is considered on target or reset it
+		/// to the base unit by passing `None`.
+		///
+		/// The supply is only expanded below `low` and only contracted above `high`.
+		/// Note: The band is not adjusted by `repeg`.
+		///
+		/// Can only be called by root.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 2 writes
+		pub fn set_target_band(origin, band: Option<(Coins, Coins)>) -> DispatchResult {
+			ensure_root(origin)?;
+			if let Some((low, high)) = band {
+				ensure!(!low.is_zero() && low <= high, Error::<T>::InvalidTargetBand);
+			}
+			// ↑ verify ↑
+			// ↓ update ↓
+			<TargetBandLow>::set(band.map(|(low, _high)| low));
+			<TargetBandHigh>::set(band.map(|(_low, high)| high));
+			let (low, high) = Self::target_band();
+			Self::deposit_event(RawEvent::NewTargetBand(low, high));
+
+			Ok(())
+		}
+
+		/// Adjust the supply at the current price without waiting for the next adjustment block.
+		///
+		/// Can be called by anyone (e.g., keepers), but only once per block and not in a block in
+		/// which the supply was already adjusted. Emits the events of the supply adjustment.
+		///
+		/// Uses the price recorded for this block (`CoinPrice::fetch_price` does not record a new
+		/// one). The block only counts as adjusted if the rebase succeeds.
+		///
+		/// **Weight:**
+		/// - complexity: `O(F + R)`
+		///   - `F` being the complexity of `CoinPrice::fetch_price()`
+		///   - `R` being the complexity of `rebase_on_price`
+		/// - DB access: 1 read and 1 write of `LastRebaseBlock` + the DB access of the rebase
+		/// - declared weight: see `rebase_weight`
+		#[weight = FunctionOf(|_: ()| <Module<T>>::rebase_weight(), DispatchClass::Normal, true)]
+		pub fn poke(origin) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			let now = <system::Module<T>>::block_number();
+			ensure!(Self::last_rebase_block() != Some(now), Error::<T>::AlreadyRebased);
+			let price = T::CoinPrice::fetch_price();
+			// ↑ verify ↑
+			// ↓ update ↓
+			Self::rebase_on_price(now, price)?;
+			<LastRebaseBlock<T>>::put(now);
+			Ok(())
+		}
+
+		/// Migrate the storage to the layout of `PALLET_VERSION`.
+		///
+		/// There are no migrations yet, so older versions are only tagged with the current one.
+		///
+		/// **Weight:**
+		/// - complexity: `O(1)`
+		/// - DB access: 1 read and potentially 1 write of `StorageVersion`
+		fn on_runtime_upgrade() -> Weight {
+			let version = Self::storage_version();
+			if version > PALLET_VERSION {
+				native::error!(
+					"storage version {} is newer than the pallet version {} --> skipping migration",
+					version,
+					PALLET_VERSION
+				);
+				return 0;
+			}
+			if version < PALLET_VERSION {
+				native::info!("migrating storage from version {} to {}", version, PALLET_VERSION);
+				<StorageVersion>::put(PALLET_VERSION);
+			}
+			0
+		}
+
+		/// Record the price of block `n` and adjust the amount of Coins according to the price.
+		///
+		/// Skips the adjustment if it already ran for block `n`.
+		///
+		/// **Weight:**
+		/// - complexity: `O(F + P)`
+		///   - `F` being the complexity of `CoinPrice::record_price()` and `CoinPrice::fetch_price()`
+		///   - `P` being the complexity of `on_block_with_price`
+		/// - DB access: read and potentially write `LastRebaseBlock` on adjustment blocks
+		/// - returned weight: see `on_initialize_weight`
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			// recorded in every block, even if the adjustment is skipped
+			T::CoinPrice::record_price();
+			if n % T::AdjustmentFrequency::get() == 0.into() {
+				if Self::last_rebase_block() == Some(n) {
+					native::warn!("supply was already adjusted in block {:?} --> skipping", n);
+					return 0;
+				}
+				<LastRebaseBlock<T>>::put(n);
+			}
+			// computed before adjusting because the adjustment may consume the queues
+			let weight = Self::on_initialize_weight(n);
+			let price = T::CoinPrice::fetch_price();
+			Self::on_block_with_price(n, price).unwrap_or_else(|e| {
+				native::error!("could not adjust supply: {:?}", e);
+			});
+			weight
+		}
+	}
+}
+
+// Implement the BasicCurrency to allow other pallets to interact programmatically
+// with the Stablecoin.
+//
+// Only the supply adjustments change the issuance, so `deposit` and `withdraw` always fail.
+// Rebases do not go through this interface: expansions credit bond holders and shareholders
+// directly and contractions burn the Coins of filled bids, so balances and `total_issuance`
+// read through this trait can change between blocks without any calls to it.
+impl<T: Trait> BasicCurrency<T::AccountId> for Module<T> {
+	type Balance = Coins;
+
+	/// Return the amount of Coins in circulation.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read
+	fn total_issuance() -> Self::Balance {
+		Self::coin_supply()
+	}
+
+	/// Return the balance of the given account.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read from balance storage map
+	fn total_balance(who: &T::AccountId) -> Self::Balance {
+		Self::get_balance(who)
+	}
+
+	/// Return the free balance of the given account.
+	///
+	/// Equal to `total_balance` for this stablecoin.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read from balance storage map
+	fn free_balance(who: &T::AccountId) -> Self::Balance {
+		Self::get_balance(who)
+	}
+
+	/// Cannot withdraw from stablecoin accounts. Returns `Ok(())` if `amount` is 0, otherwise returns an error.
+	fn ensure_can_withdraw(_who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		Err(DispatchError::Other("cannot change issuance for stablecoins"))
+	}
+
+	/// Transfer `amount` from one account to another.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 2 reads and write from and to balance storage map
+	fn transfer(from: &T::AccountId, to: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		Self::transfer_from_to(from, to, amount)
+	}
+
+	/// Noop that returns an error. Cannot change the issuance of a stablecoin.
+	fn deposit(_who: &T::AccountId, _amount: Self::Balance) -> DispatchResult {
+		Err(DispatchError::Other("cannot change issuance for stablecoins"))
+	}
+
+	/// Noop that returns an error. Cannot change the issuance of a stablecoin.
+	fn withdraw(_who: &T::AccountId, _amount: Self::Balance) -> DispatchResult {
+		Err(DispatchError::Other("cannot change issuance for stablecoins"))
+	}
+
+	/// Test whether the given account can be slashed with `value`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read from balance storage map
+	fn can_slash(who: &T::AccountId, value: Self::Balance) -> bool {
+		if value.is_zero() {
+			return true;
+		}
+		Self::get_balance(who) >= value
+	}
+
+	/// Slash account `who` by `amount` returning the actual amount slashed.
 	///
 	/// If the account does not have `amount` Coins it will be slashed to 0
 	/// and that amount returned.
@@ -579,16 +1613,143 @@ impl<T: Trait> BasicCurrency<T::AccountId> for Module<T> {
 				*b = b.saturating_sub(amount);
 			}
 		});
+		Self::deposit_event(RawEvent::Withdraw(who.clone(), amount - remaining));
 		remaining
 	}
 }
 
+/// The custom `InvalidTransaction` code of bids the signer cannot pay for.
+pub const UNAFFORDABLE_BID: u8 = 1;
+
+/// Signed extension rejecting `bid_for_bond`, `bid_for_bond_simple` and `bid_ladder` transactions
+/// whose payment the signer cannot afford, so they do not enter the transaction pool.
+///
+/// Only checks the balance, the remaining checks happen on dispatch.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckBidAffordable<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckBidAffordable<T> {
+	/// Create a new instance of the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Trait + Send + Sync> Default for CheckBidAffordable<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for CheckBidAffordable<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckBidAffordable")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckBidAffordable<T>
+where
+	<T as system::Trait>::Call: IsSubType<Module<T>, T>,
+{
+	const IDENTIFIER: &'static str = "CheckBidAffordable";
+	type AccountId = T::AccountId;
+	type Call = <T as system::Trait>::Call;
+	type AdditionalSigned = ();
+	type DispatchInfo = DispatchInfo;
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	/// Reject the transaction if it is a bid (or bid ladder) the signer cannot pay for.
+	///
+	/// A bid ladder whose total payment overflows is rejected as well.
+	///
+	/// **Weight:**
+	/// - complexity: `O(L)`
+	///   - `L` being the number of bids in a bid ladder
+	/// - DB access: 1 storage map read
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: Self::DispatchInfo,
+		_len: usize,
+	) -> TransactionValidity {
+		let payment = match call.is_sub_type() {
+			Some(Call::bid_for_bond(price, quantity, _))
+			| Some(Call::bid_for_bond_simple(price, quantity)) => *price * *quantity,
+			Some(Call::bid_ladder(bids)) => {
+				let total = bids.iter().try_fold(0 as Coins, |total, (price, quantity)| {
+					total.checked_add(*price * *quantity)
+				});
+				match total {
+					Some(total) => total,
+					None => return InvalidTransaction::Custom(UNAFFORDABLE_BID).into(),
+				}
+			}
+			_ => return Ok(ValidTransaction::default()),
+		};
+		if Module::<T>::get_balance(who) < payment {
+			return InvalidTransaction::Custom(UNAFFORDABLE_BID).into();
+		}
+		Ok(ValidTransaction::default())
+	}
+}
+
 impl<T: Trait> Module<T> {
+	/// Return the amount of Coins that are meant to track the value.
+	///
+	/// Uses the `BaseUnitOverride` set by `repeg` if present and falls back to `BaseUnit` otherwise.
+	pub fn base_unit() -> Coins {
+		Self::base_unit_override().unwrap_or_else(T::BaseUnit::get)
+	}
+
+	/// Return the band of prices `(low, high)` that is considered on target.
+	///
+	/// Both ends default to the `base_unit` if no band was set with `set_target_band`.
+	pub fn target_band() -> (Coins, Coins) {
+		let base_unit = Self::base_unit();
+		(
+			Self::target_band_low().unwrap_or(base_unit),
+			Self::target_band_high().unwrap_or(base_unit),
+		)
+	}
+
 	// ------------------------------------------------------------
 	// balances
 
+	/// Check `amount` against the `MinimumTransfer` and return the amount to send from `from`.
+	///
+	/// Returns the whole balance of `from` if sending `amount` would leave less than
+	/// `MinimumTransfer` (but more than zero) Coins and `KeepAlive` is not set.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 storage map read
+	fn amount_without_dust(from: &T::AccountId, amount: Coins) -> Result<Coins, DispatchError> {
+		let minimum = T::MinimumTransfer::get();
+		ensure!(amount >= minimum, Error::<T>::BelowMinimumTransfer);
+		let balance = Self::get_balance(from);
+		let left = balance.checked_sub(amount).ok_or(Error::<T>::InsufficientBalance)?;
+		if left == 0 || left >= minimum {
+			return Ok(amount);
+		}
+		ensure!(!T::KeepAlive::get(), Error::<T>::WouldLeaveDust);
+		Ok(balance)
+	}
+
 	/// Transfer `amount` of Coins from one account to another.
 	///
+	/// Transfers from an account to itself only check the balance and do not touch storage.
+	///
 	/// **Weight:**
 	/// - complexity: `O(1)`
 	/// - DB access: 2 storage map reads + 2 storage map writes
@@ -597,6 +1758,9 @@ impl<T: Trait> Module<T> {
 		let updated_from_balance = from_balance
 			.checked_sub(amount)
 			.ok_or(Error::<T>::InsufficientBalance)?;
+		if from == to {
+			return Ok(());
+		}
 		let receiver_balance = Self::get_balance(&to);
 		let updated_to_balance = receiver_balance
 			.checked_add(amount)
@@ -615,6 +1779,8 @@ impl<T: Trait> Module<T> {
 
 	/// Add `amount` Coins to the balance for `account`.
 	///
+	/// Emits a `Deposit` event.
+	///
 	/// **Weight:**
 	/// - complexity: `O(1)`
 	/// - DB access: 1 write to balance storage map
@@ -623,10 +1789,13 @@ impl<T: Trait> Module<T> {
 			*b = b.saturating_add(amount);
 			*b
 		});
+		Self::deposit_event(RawEvent::Deposit(account.clone(), amount));
 	}
 
 	/// Remove `amount` Coins from the balance of `account`.
 	///
+	/// Emits a `Withdraw` event.
+	///
 	/// **Weight:**
 	/// - complexity: `O(1)`
 	/// - DB access: 1 write to balance storage map
@@ -634,81 +1803,263 @@ impl<T: Trait> Module<T> {
 		<Balance<T>>::try_mutate(&account, |b: &mut u64| -> DispatchResult {
 			*b = b.checked_sub(amount).ok_or(Error::<T>::InsufficientBalance)?;
 			Ok(())
-		})
+		})?;
+		Self::deposit_event(RawEvent::Withdraw(account.clone(), amount));
+		Ok(())
 	}
 
 	// ------------------------------------------------------------
 	// bids
 
 	/// Construct a transient storage adapter for the bids priority queue.
-	fn bids_transient() -> BoundedPriorityQueue<Bid<T::AccountId>, <Self as Store>::BondBids, T::MaximumBids>
-	{
-		BoundedPriorityQueue::<Bid<T::AccountId>, <Self as Store>::BondBids, T::MaximumBids>::new()
+	fn bids_transient() -> BoundedPriorityQueue<
+		Bid<T::AccountId, T::BlockNumber>,
+		<Self as Store>::BondBids,
+		MaximumBidsLimit<T>,
+	> {
+		BoundedPriorityQueue::<
+			Bid<T::AccountId, T::BlockNumber>,
+			<Self as Store>::BondBids,
+			MaximumBidsLimit<T>,
+		>::new()
 	}
 
-	/// Add a bid to the queue.
+	/// Create a new bid for the given `account` placed in the current block.
+	///
+	/// Assigns the next sequence number to the bid.
 	///
 	/// **Weight:**
-	/// - complexity: `O(B)` with `B` being the amount of bids
-	/// - DB access:
-	///   - read and write `B` bids
-	///   - potentially call 1 `refund_bid`
-	fn add_bid(bid: Bid<T::AccountId>) {
-		Self::bids_transient()
-			.push(bid)
-			.map(|to_refund| Self::refund_bid(&to_refund));
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read and 1 write for the bid sequence
+	fn new_bid(account: T::AccountId, price: Perbill, quantity: Coins) -> Bid<T::AccountId, T::BlockNumber> {
+		let sequence = <BidSequence>::mutate(|next| {
+			let sequence = *next;
+			*next = next.wrapping_add(1);
+			sequence
+		});
+		Bid::new(account, price, quantity, <system::Module<T>>::block_number(), sequence)
 	}
 
-	/// Refund the Coins payed for `bid` to the account that bid.
+	/// Check the `price` and `quantity` of a bid against the configured limits.
+	///
+	/// Valid prices are in the range `(MinimumBondPrice, 100%]`.
+	/// The quantity needs to be at least `BaseUnit`, which also applies to every bond the bid is
+	/// split into when filled completely (see `new_bonds_for_bid`).
+	fn ensure_valid_bid(price: Perbill, quantity: Coins) -> DispatchResult {
+		// `Perbill` constructors clamp to 100%, but a decoded `Perbill` (e.g., from an extrinsic)
+		// is not checked and can exceed 100%.
+		ensure!(price <= Perbill::one(), Error::<T>::BondPriceOver100Percent);
+		ensure!(price > T::MinimumBondPrice::get(), Error::<T>::BondPriceTooLow);
+		ensure!(quantity >= Self::base_unit(), Error::<T>::BondQuantityTooLow);
+		// The last bond of a split bid only receives the remainder of the quantity.
+		let max_size = max(1, T::MaximumBondSize::get());
+		let smallest_bond = match quantity % max_size {
+			0 => min(quantity, max_size),
+			remainder => remainder,
+		};
+		ensure!(smallest_bond >= Self::base_unit(), Error::<T>::BondQuantityTooLow);
+		Ok(())
+	}
+
+	/// Return the bid intake of the current block including `payment`.
+	///
+	/// Returns `BidIntakeExceeded` if the intake would exceed `MaxBidIntakePerBlock`.
+	/// An overflow of the intake is treated as exceeding the maximum as well.
 	///
 	/// **Weight:**
 	/// - complexity: `O(1)`
-	/// - DB access: 1 write
-	fn refund_bid(bid: &Bid<T::AccountId>) {
-		Self::add_balance(&bid.account, bid.payment());
-		Self::deposit_event(RawEvent::RefundedBid(bid.account.clone(), bid.payment()));
+	/// - DB access: 1 read
+	fn checked_bid_intake(payment: Coins) -> Result<Coins, DispatchError> {
+		let (block, intake) = Self::bid_intake();
+		let intake = if block == <system::Module<T>>::block_number() {
+			intake
+		} else {
+			0
+		};
+		let intake = intake
+			.checked_add(payment)
+			.ok_or(Error::<T>::BidIntakeExceeded)?;
+		ensure!(
+			intake <= T::MaxBidIntakePerBlock::get(),
+			Error::<T>::BidIntakeExceeded
+		);
+		Ok(intake)
 	}
 
-	/// Cancel all bids where `cancel_for` returns true and refund the bidders.
+	/// Store `intake` as the bid intake of the current block.
 	///
 	/// **Weight:**
-	/// - complexity: `O(B)` with `B` being the amount of bids
-	/// - DB access:
-	///   - read and write `B` bids
-	///   - call `refund_bid` up to `B` times
-	fn cancel_bids<F>(cancel_for: F)
-	where
-		F: Fn(&Bid<T::AccountId>) -> bool,
-	{
-		let mut bids = Self::bond_bids();
-
-		bids.retain(|b| {
-			if cancel_for(b) {
-				Self::refund_bid(b);
-				return false;
-			}
-			true
-		});
-
-		<BondBids<T>>::put(bids);
+	/// - complexity: `O(1)`
+	/// - DB access: 1 write
+	fn update_bid_intake(intake: Coins) {
+		<BidIntake<T>>::put((<system::Module<T>>::block_number(), intake));
 	}
 
-	/// Tries to contract the supply by `amount` by converting bids to bonds.
+	/// Ensure that the bid queue can hold bids (i.e., the maximum amount of bids is not zero).
 	///
-	/// Note: Could contract the supply by less than `amount` if there are not enough bids.
+	/// Otherwise every new bid would be removed (and refunded) right away.
 	///
 	/// **Weight:**
-	/// - complexity: `O(BI + BO + C)`
-	///   - `BI` being the number of bids in the bidding auction, limited to `MaximumBids`
-	///   - `BO` being the number of newly created bonds, limited to `BI`
-	///   - `C` being a constant amount of storage reads and writes for coin supply and bonds queue bounds bookkeeping
-	/// - DB access:
-	///   - 1 write for `coin_supply`
-	///   - read and write bids
-	///   - write `BO` newly created bonds + read and write bonds queue bounds
-	///   - potentially refund up to `BI` bids
-	fn contract_supply(coin_supply: Coins, amount: Coins) -> DispatchResult {
-		// Checking whether coin supply would underflow.
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read for the maximum bids override
+	fn ensure_bidding_enabled() -> DispatchResult {
+		ensure!(MaximumBidsLimit::<T>::get() > 0, Error::<T>::BiddingDisabled);
+		Ok(())
+	}
+
+	/// Ensure that at least `BidCooldown` blocks passed since `who` last placed bids.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read
+	fn ensure_bid_cooldown(who: &T::AccountId) -> DispatchResult {
+		let cooldown = T::BidCooldown::get();
+		if cooldown.is_zero() {
+			return Ok(());
+		}
+		if let Some(last) = Self::last_bid_at(who) {
+			let now = <system::Module<T>>::block_number();
+			ensure!(now >= last + cooldown, Error::<T>::BidOnCooldown);
+		}
+		Ok(())
+	}
+
+	/// Ensure that at least `ShareTransferCooldown` blocks passed since `who` last transferred shares.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read
+	fn ensure_share_transfer_cooldown(who: &T::AccountId) -> DispatchResult {
+		let cooldown = T::ShareTransferCooldown::get();
+		if cooldown.is_zero() {
+			return Ok(());
+		}
+		if let Some(last) = Self::last_share_transfer_at(who) {
+			let now = <system::Module<T>>::block_number();
+			ensure!(now >= last + cooldown, Error::<T>::ShareTransferOnCooldown);
+		}
+		Ok(())
+	}
+
+	/// Add a bid to the queue and its payment to the bid escrow.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the amount of bids
+	/// - DB access:
+	///   - read and write `B` bids
+	///   - read and write the bid escrow
+	///   - potentially call 1 `refund_bid`
+	fn add_bid(bid: Bid<T::AccountId, T::BlockNumber>) {
+		<BidEscrow>::mutate(|escrow| *escrow = escrow.saturating_add(bid.payment()));
+		Self::bids_transient()
+			.push(bid)
+			.map(|to_refund| Self::refund_bid(&to_refund));
+	}
+
+	/// Refund the Coins payed for `bid` to the account that bid.
+	///
+	/// Refunds at most the Coins left in the bid escrow so no unbacked Coins are created. Emits
+	/// `PartialRefund` if that is less than the payment of the bid.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access:
+	///   - read and write the bid escrow
+	///   - 1 write for the balance
+	fn refund_bid(bid: &Bid<T::AccountId, T::BlockNumber>) -> Coins {
+		let payment = bid.payment();
+		let escrow = Self::bid_escrow();
+		let refund = min(payment, escrow);
+		<BidEscrow>::put(escrow - refund);
+		if refund < payment {
+			native::warn!(
+				"bid escrow short by {} Coins --> partially refunding bid: {:?}",
+				payment - refund,
+				bid
+			);
+			Self::deposit_event(RawEvent::PartialRefund(bid.account.clone(), payment, refund));
+		}
+		Self::add_balance(&bid.account, refund);
+		Self::deposit_event(RawEvent::RefundedBid(bid.account.clone(), refund));
+		refund
+	}
+
+	/// Remove `amount` Coins of filled bids from the bid escrow.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: read and write the bid escrow
+	fn release_bid_escrow(amount: Coins) {
+		<BidEscrow>::mutate(|escrow| *escrow = escrow.saturating_sub(amount));
+	}
+
+	/// Cancel all bids where `cancel_for` returns true and refund the bidders.
+	///
+	/// Returns the total amount of Coins refunded.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the amount of bids
+	/// - DB access:
+	///   - read and write `B` bids
+	///   - call `refund_bid` up to `B` times
+	fn cancel_bids<F>(cancel_for: F) -> Coins
+	where
+		F: Fn(&Bid<T::AccountId, T::BlockNumber>) -> bool,
+	{
+		let mut bids = Self::bond_bids();
+		let mut refunded: Coins = 0;
+
+		bids.retain(|b| {
+			if cancel_for(b) {
+				refunded = refunded.saturating_add(Self::refund_bid(b));
+				return false;
+			}
+			true
+		});
+
+		<BondBids<T>>::put(bids);
+		refunded
+	}
+
+	/// Remove the lowest bids until at most `maximum` bids are left and refund the bidders.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the amount of bids
+	/// - DB access:
+	///   - read and write `B` bids
+	///   - call `refund_bid` up to `B` times
+	fn truncate_bids(maximum: u64) {
+		let mut bids = Self::bond_bids();
+		let excess = (bids.len() as u64).saturating_sub(maximum) as usize;
+		// the lowest bids are stored first
+		for bid in bids.drain(..excess) {
+			Self::refund_bid(&bid);
+		}
+		<BondBids<T>>::put(bids);
+	}
+
+	/// Tries to contract the supply by `amount` by converting bids to bonds.
+	///
+	/// The bids are matched with `match_bids`. If there are not enough bids the rest is burned
+	/// from the `ContractionReserve` (if configured) up to its balance.
+	///
+	/// Note: Could contract the supply by less than `amount` if there are not enough bids.
+	///
+	/// Returns the amount of Coins that were actually burned.
+	///
+	/// **Weight:**
+	/// - complexity: `O(BI + BO + C)`
+	///   - `BI` being the number of bids in the bidding auction, limited to `MaximumBids`
+	///   - `BO` being the number of newly created bonds, limited to `BI`
+	///   - `C` being a constant amount of storage reads and writes for coin supply and bonds queue bounds bookkeeping
+	/// - DB access:
+	///   - 1 write for `coin_supply`
+	///   - read and write bids
+	///   - write `BO` newly created bonds + read and write bonds queue bounds
+	///   - potentially refund up to `BI` bids
+	///   - potentially 1 read and 1 write for the balance of the `ContractionReserve`
+	fn contract_supply(coin_supply: Coins, amount: Coins) -> Result<Coins, DispatchError> {
+		// Checking whether coin supply would underflow.
 		let remaining_supply = coin_supply
 			.checked_sub(amount)
 			.ok_or(Error::<T>::CoinSupplyUnderflow)?;
@@ -716,37 +2067,37 @@ impl<T: Trait> Module<T> {
 			return Err(DispatchError::from(Error::<T>::CoinSupplyUnderflow));
 		}
 		// ↑ verify ↑
-		let mut bids = Self::bids_transient();
-		let mut remaining = amount;
+		let mut bids = Self::bond_bids();
+		let (matches, mut remaining) = Self::match_bids(&mut bids, amount);
+		// the Coins payed by the filled bids
+		let mut filled: Coins = 0;
 		let mut new_bonds = VecDeque::new();
 		// ↓ update ↓
-		while remaining > 0 && !bids.is_empty() {
-			let mut bid = bids
-				.pop()
-				.expect("checked whether queue is empty on previous line; qed");
-			// the current bid can cover all the remaining contraction
-			if bid.payment() >= remaining {
-				match bid.remove_coins(remaining) {
-					Err(_e) => {
-						native::warn!("unable to remove coins from bid --> refunding bid: {:?}", bid);
-						Self::refund_bid(&bid);
-					}
-					Ok(removed_quantity) => {
-						new_bonds.push_back(Self::new_bond(bid.account.clone(), removed_quantity));
-						// re-add bid with reduced amount
-						if bid.quantity > 0 {
-							bids.push(bid).map(|to_refund| Self::refund_bid(&to_refund));
-						}
-						remaining = 0;
-					}
+		for bid_match in matches {
+			match bid_match {
+				BidMatch::Filled(bid, payment, payout) => {
+					Self::release_bid_escrow(payment);
+					filled += payment;
+					new_bonds.extend(Self::new_bonds_for_bid(&bid, payout, payment));
+					Self::deposit_event(RawEvent::BidFilled(bid.account, bid.sequence, payment));
+				}
+				BidMatch::Unfillable(bid) => {
+					native::warn!("unable to remove coins from bid --> refunding bid: {:?}", bid);
+					Self::refund_bid(&bid);
+				}
+			}
+		}
+		<BondBids<T>>::put(bids);
+		// not enough bids --> burn from the reserve as a last resort
+		let mut burned_directly = 0;
+		if remaining > 0 {
+			if let Some(reserve) = T::ContractionReserve::get() {
+				burned_directly = min(remaining, Self::get_balance(&reserve));
+				if burned_directly > 0 {
+					Self::remove_balance(&reserve, burned_directly)?;
+					remaining -= burned_directly;
+					Self::deposit_event(RawEvent::DirectContraction(burned_directly));
 				}
-			} else {
-				let payment = bid.payment();
-				let Bid {
-					account, quantity, ..
-				} = bid;
-				new_bonds.push_back(Self::new_bond(account, quantity));
-				remaining -= payment;
 			}
 		}
 		debug_assert!(
@@ -754,26 +2105,122 @@ impl<T: Trait> Module<T> {
 			"remaining is never greater than the original amount"
 		);
 		let burned = amount.saturating_sub(remaining);
+		debug_assert!(
+			filled + burned_directly == burned,
+			"burned coins should equal the coins payed by filled bids and burned from the reserve"
+		);
 		debug_assert!(
 			burned <= coin_supply,
 			"burned <= amount < coin_supply is checked by coin underflow check in first lines"
 		);
 		let new_supply = coin_supply.saturating_sub(burned);
+		// Bonds are bought at a discount so they always promise at least the burned Coins.
+		let total_payout = new_bonds
+			.iter()
+			.fold(0, |total: Coins, bond| total.saturating_add(bond.payout));
+		debug_assert!(
+			total_payout >= burned - burned_directly,
+			"bonds should pay out at least the amount of burned coins"
+		);
+		#[cfg(feature = "paranoid")]
+		assert!(
+			total_payout >= burned - burned_directly,
+			"bonds should pay out at least the amount of burned coins"
+		);
+		// `new_bonds_for_bid` creates no bonds for a zero payout and filling a bid never removes
+		// a zero quantity for a non-zero payment because the price is at most 1
+		debug_assert!(
+			new_bonds.iter().all(|bond| bond.payout > 0),
+			"bonds with zero payout should never be created"
+		);
+		#[cfg(feature = "paranoid")]
+		assert!(
+			new_bonds.iter().all(|bond| bond.payout > 0),
+			"bonds with zero payout should never be created"
+		);
 		for bond in new_bonds.iter() {
 			Self::deposit_event(RawEvent::NewBond(
 				bond.account.clone(),
 				bond.payout,
 				bond.expiration,
+				bond.bid_sequence,
 			));
 		}
-		let mut bonds = Self::bonds_transient();
-		for bond in new_bonds {
-			bonds.push_back(bond);
-		}
+		Self::bonds_transient().push_back_multiple(new_bonds);
 		<CoinSupply>::put(new_supply);
 		native::info!("contracted supply by: {}", burned);
 		Self::deposit_event(RawEvent::ContractedSupply(burned));
-		Ok(())
+		Ok(burned)
+	}
+
+	/// Match the bids against a contraction by `amount`, filling the highest bids first.
+	///
+	/// `bids` are sorted from lowest to highest like `BondBids`. Matched bids are removed and the
+	/// rest of a partially filled bid is put back. Returns the outcome for every matched bid in
+	/// order and the part of `amount` that the bids do not cover.
+	///
+	/// Shared by `contract_supply` and the queries simulating it so they always agree:
+	/// + Stops once the bonds for the next bid (split at `MaximumBondSize`) would exceed `MaxBonds`.
+	///   The limit is checked against the whole quantity of the bid, even if it would only be
+	///   filled partially. Rejecting fills (instead of evicting the oldest bonds) keeps existing
+	///   claims intact.
+	/// + The rest of a partially filled bid that is worth less than a Coin is added to its bonds.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bonds queue bounds
+	fn match_bids(
+		bids: &mut Vec<Bid<T::AccountId, T::BlockNumber>>,
+		amount: Coins,
+	) -> (Vec<BidMatch<T::AccountId, T::BlockNumber>>, Coins) {
+		let free_slots = Self::max_bonds().saturating_sub(Self::bond_count() as usize);
+		let mut bonds_created = 0;
+		let mut remaining = amount;
+		let mut matches = Vec::new();
+		while remaining > 0 {
+			let mut bid = match bids.pop() {
+				Some(bid) => bid,
+				None => break,
+			};
+			if bonds_created + Self::bonds_needed(bid.quantity) > free_slots {
+				native::warn!("bond queue is full --> not filling further bids");
+				bids.push(bid);
+				break;
+			}
+			// the current bid can cover all the remaining contraction
+			if bid.payment() >= remaining {
+				let removed_quantity = match bid.remove_coins(remaining) {
+					Ok(removed_quantity) => removed_quantity,
+					Err(_e) => {
+						matches.push(BidMatch::Unfillable(bid));
+						continue;
+					}
+				};
+				// The rest of the bid is worth less than a Coin because of rounding. It could
+				// never be payed for, so it is added to the bond as a free fragment.
+				let is_dust = bid.quantity > 0 && bid.payment().is_zero();
+				let payout = if is_dust {
+					removed_quantity + bid.quantity
+				} else {
+					removed_quantity
+				};
+				bonds_created += Self::bonds_needed(payout);
+				// re-add bid with reduced amount (still the highest bid)
+				if bid.quantity > 0 && !is_dust {
+					bids.push(bid.clone());
+				}
+				matches.push(BidMatch::Filled(bid, remaining, payout));
+				// the bid covered all of the remaining contraction
+				remaining = 0;
+			} else {
+				let payment = bid.payment();
+				let payout = bid.quantity;
+				bonds_created += Self::bonds_needed(payout);
+				matches.push(BidMatch::Filled(bid, payment, payout));
+				remaining -= payment;
+			}
+		}
+		(matches, remaining)
 	}
 
 	// ------------------------------------------------------------
@@ -782,14 +2229,134 @@ impl<T: Trait> Module<T> {
 	/// Create a new bond for the given `account` with the given `payout`.
 	///
 	/// Expiration is calculated based on the current `block_number` and the configured
-	/// `ExpirationPeriod`.
+	/// `ExpirationPeriod`. Bonds never expire (expiration at the maximum block number) if
+	/// `BondsExpire` is disabled.
+	///
+	/// Expects `payout` to be at most `MaximumBondSize` (see `new_bonds_for_bid` for splitting).
 	fn new_bond(account: T::AccountId, payout: Coins) -> Bond<T::AccountId, T::BlockNumber> {
-		let expiration = <system::Module<T>>::block_number() + T::ExpirationPeriod::get();
+		debug_assert!(
+			payout <= T::MaximumBondSize::get(),
+			"bond payout should not exceed the maximum bond size"
+		);
+		let expiration = if T::BondsExpire::get() {
+			<system::Module<T>>::block_number() + T::ExpirationPeriod::get()
+		} else {
+			T::BlockNumber::max_value()
+		};
 		Bond {
 			account,
 			payout,
 			expiration,
+			bid_sequence: 0,
+			paid: 0,
+		}
+	}
+
+	/// Create new bonds with the given total `payout` for the account of `bid`, linked to `bid`
+	/// by its sequence number. `paid` is the amount of Coins payed for the bonds.
+	///
+	/// The payout is split into multiple bonds of at most `MaximumBondSize`, the paid Coins are
+	/// split proportionally. No bond is created for a zero payout.
+	fn new_bonds_for_bid(
+		bid: &Bid<T::AccountId, T::BlockNumber>,
+		payout: Coins,
+		paid: Coins,
+	) -> Vec<Bond<T::AccountId, T::BlockNumber>> {
+		let max_size = max(1, T::MaximumBondSize::get());
+		let mut bonds = Vec::new();
+		let mut remaining = payout;
+		let mut remaining_paid = paid;
+		while remaining > 0 {
+			let size = min(remaining, max_size);
+			// cannot overflow because the result is at most `remaining_paid`
+			let bond_paid = (remaining_paid as u128 * size as u128 / remaining as u128) as Coins;
+			bonds.push(Bond {
+				bid_sequence: bid.sequence,
+				paid: bond_paid,
+				..Self::new_bond(bid.account.clone(), size)
+			});
+			remaining -= size;
+			remaining_paid -= bond_paid;
+		}
+		bonds
+	}
+
+	/// Return `MaxBonds` clamped to the capacity of the bond queue (`BondIndex::max_value()`).
+	///
+	/// A full queue writes over its oldest bonds, so more bonds are never allowed.
+	fn max_bonds() -> usize {
+		min(T::MaxBonds::get(), BondIndex::max_value() as u32) as usize
+	}
+
+	/// Return the number of bonds created by `new_bonds_for_bid` for a `payout`.
+	fn bonds_needed(payout: Coins) -> usize {
+		let max_size = max(1, T::MaximumBondSize::get());
+		// cannot overflow because `max_size >= 1`
+		(payout / max_size + if payout % max_size > 0 { 1 } else { 0 }) as usize
+	}
+
+	/// Pay out `amount` Coins of a bond to `account` and emit `BondReleased`.
+	///
+	/// Places a bid with the Coins if `account` enabled `AutoRebid` and credits the balance
+	/// otherwise. The rest of the Coins that cannot be used for the bid (e.g., due to rounding)
+	/// is credited to the balance.
+	///
+	/// The bid is validated like in `bid_for_bond`: it is subject to the `BidCooldown` of the
+	/// account (and starts it) and counts towards `MaxBidIntakePerBlock`. The whole amount is
+	/// credited to the balance if the bid is not valid.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access:
+	///   - 1 read for the auto rebid setting
+	///   - 1 read for the last bid and 1 read for the bid intake
+	///   - read and write bids + 1 potential refund
+	///   - 1 write for the bid intake, the last bid and the bid sequence
+	///   - 1 write for the balance
+	fn pay_out_bond(account: &T::AccountId, amount: Coins) {
+		Self::deposit_event(RawEvent::BondReleased(account.clone(), amount));
+		let price = match Self::auto_rebid(account) {
+			Some(price) if !price.is_zero() => price,
+			_ => return Self::add_balance(account, amount),
+		};
+		// The inverse price converts the payment coins to the bid quantity (see `Bid::remove_coins`).
+		let inverse_price: Ratio<u64> = Ratio::new(Perbill::ACCURACY.into(), price.deconstruct().into());
+		let quantity = inverse_price
+			.checked_mul(&amount.into())
+			.map(|r| r.to_integer())
+			.unwrap_or(0);
+		let payment = price * quantity;
+		let intake = match Self::checked_auto_rebid(account, price, quantity, payment, amount) {
+			Ok(intake) => intake,
+			Err(_) => return Self::add_balance(account, amount),
+		};
+		if payment < amount {
+			Self::add_balance(account, amount - payment);
 		}
+		Self::update_bid_intake(intake);
+		<LastBidAt<T>>::insert(account, <system::Module<T>>::block_number());
+		Self::add_bid(Self::new_bid(account.clone(), price, quantity));
+		Self::deposit_event(RawEvent::NewBid(account.clone(), price, quantity));
+	}
+
+	/// Check an automatic bid of `account` for `quantity` at `price` with a `payment` out of a
+	/// bond payout of `amount` Coins and return the bid intake including it.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read for the maximum bids, the last bid and the bid intake each
+	fn checked_auto_rebid(
+		account: &T::AccountId,
+		price: Perbill,
+		quantity: Coins,
+		payment: Coins,
+		amount: Coins,
+	) -> Result<Coins, DispatchError> {
+		ensure!(payment <= amount, Error::<T>::InsufficientBalance);
+		Self::ensure_bidding_enabled()?;
+		Self::ensure_valid_bid(price, quantity)?;
+		Self::ensure_bid_cooldown(account)?;
+		Self::checked_bid_intake(payment)
 	}
 
 	/// Create a new transient storage adapter that manages the bonds.
@@ -809,13 +2376,142 @@ impl<T: Trait> Module<T> {
 		>::new()
 	}
 
+	/// Iterate over the bonds in the bonds queue from front to back.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bonds
+	/// - DB access: read bonds queue bounds + `B` bond reads
+	fn bonds() -> impl Iterator<Item = Bond<T::AccountId, T::BlockNumber>> {
+		let (start, length) = Self::bonds_range();
+		(0..length).map(move |i| Self::get_bond(start.wrapping_add(i)))
+	}
+
+	/// Create a new transient storage adapter that manages the rebase history.
+	fn rebase_history_transient() -> BoundedDeque<
+		(T::BlockNumber, SupplyAction),
+		<Self as Store>::RebaseHistoryRange,
+		<Self as Store>::RebaseHistory,
+		RebaseIndex,
+	> {
+		BoundedDeque::<
+			(T::BlockNumber, SupplyAction),
+			<Self as Store>::RebaseHistoryRange,
+			<Self as Store>::RebaseHistory,
+			RebaseIndex,
+		>::new()
+	}
+
+	/// Refund the Coins payed for the expired `bond` to its account and return the refunded amount.
+	///
+	/// Does not update the coin supply, that is left to the caller.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 write for the balance
+	fn refund_expired_bond(bond: Bond<T::AccountId, T::BlockNumber>) -> Coins {
+		if bond.paid > 0 {
+			Self::add_balance(&bond.account, bond.paid);
+		}
+		Self::deposit_event(RawEvent::BondExpired(bond.account, bond.paid));
+		bond.paid
+	}
+
+	/// Route the unpaid `payout` of an expired bond of `account` during an expansion with
+	/// `remaining` Coins left to hand out and emit `BondExpiredUnpaid`.
+	///
+	/// Pays up to `remaining` Coins of the payout to the `ExpiredBondTreasury` if configured and
+	/// records the payout as `SavedSeigniorage` otherwise. Returns the amount payed to the treasury,
+	/// the rest of the expansion is left to the next bonds or the shareholders.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 write for the treasury balance or `SavedSeigniorage`
+	fn route_expired_payout(account: &T::AccountId, payout: Coins, remaining: Coins) -> Coins {
+		Self::deposit_event(RawEvent::BondExpiredUnpaid(account.clone(), payout));
+		match T::ExpiredBondTreasury::get() {
+			Some(treasury) => {
+				let routed = min(payout, remaining);
+				if routed > 0 {
+					Self::add_balance(&treasury, routed);
+				}
+				routed
+			}
+			None => {
+				<SavedSeigniorage>::mutate(|saved| *saved = saved.saturating_add(payout));
+				0
+			}
+		}
+	}
+
+	/// Remove up to `limit` expired bonds from the front of the bond queue, refund the Coins
+	/// payed for them and return the number of removed bonds.
+	///
+	/// Bonds are queued in the order of their expiration so all expired bonds are at the front.
+	/// The refunds are added to the supply as long as it stays within `MaxCoinSupply` (like in
+	/// `expand_supply`). Expired bonds whose refund exceeds it are skipped and kept at the front
+	/// of the queue, they count towards `limit` as well.
+	///
+	/// **Weight:**
+	/// - complexity: `O(L)` with `L` being `limit`
+	/// - DB access:
+	///   - read and write bonds queue bounds
+	///   - up to `L + 1` bond reads and removals and up to `L + 1` bond writes
+	///   - up to `L` balance writes + read and write coin supply
+	fn remove_expired_bonds(limit: u32) -> u32 {
+		let now = <system::Module<T>>::block_number();
+		let coin_supply = Self::coin_supply();
+		let mut refund_headroom = T::MaxCoinSupply::get().saturating_sub(coin_supply);
+		let mut refunded: Coins = 0;
+		let mut deferred = Vec::new();
+		let mut bonds = Self::bonds_transient();
+		let mut removed = 0;
+		for _ in 0..limit {
+			match bonds.pop_front() {
+				Some(bond) if now >= bond.expiration => {
+					if bond.paid > refund_headroom {
+						native::warn!("refunding expired bond would exceed the supply ceiling --> deferring");
+						deferred.push(bond);
+						continue;
+					}
+					refund_headroom -= bond.paid;
+					refunded += Self::refund_expired_bond(bond);
+					removed += 1;
+				}
+				Some(bond) => {
+					// reached the first live bond --> put it back and stop
+					bonds.push_front(bond);
+					break;
+				}
+				None => break,
+			}
+		}
+		// the deferred bonds expired before all remaining bonds (see `expand_supply`)
+		for bond in deferred.into_iter().rev() {
+			bonds.push_front(bond);
+		}
+		// cannot overflow because the refunds are limited by `refund_headroom`
+		<CoinSupply>::put(coin_supply + refunded);
+		removed
+	}
+
 	// ------------------------------------------------------------
 	// expand supply
 
 	/// Expand the supply by `amount` by paying out bonds and shares.
 	///
 	/// Will first pay out bonds and only pay out shares if there are no remaining
-	/// bonds.
+	/// bonds. Bonds are payed out in order or proportionally depending on `BondPayoutMode`.
+	///
+	/// If the share supply is zero the rest goes to `SeigniorageFallback` or is not created at all.
+	/// The rest is never created if `SeigniorageToShareholders` is disabled.
+	///
+	/// The expansion is capped so that the supply does not exceed `MaxCoinSupply`.
+	///
+	/// Expired bonds that are reached are discarded and the Coins payed for them are refunded.
+	/// The refunds are added to the supply on top of `amount` as long as the supply stays within
+	/// `MaxCoinSupply`. Expired bonds whose refund exceeds it are skipped (the live bonds behind
+	/// them are still payed out) and kept at the front of the queue for a later expansion. The
+	/// payouts of the refunded bonds are routed with `route_expired_payout`.
 	///
 	/// **Weight:**
 	/// - complexity: `O(B + C + H)`
@@ -824,134 +2520,745 @@ impl<T: Trait> Module<T> {
 	///   - `H` being the complexity of `hand_out_coins`
 	/// - DB access:
 	///   - read bonds + read and write bonds queue bounds
-	///   - potentially write back 1 bond
+	///   - potentially write back 1 bond and the deferred expired bonds
+	///   - 1 balance write per refunded expired bond
+	///   - 1 write to the `ExpiredBondTreasury` or `SavedSeigniorage` per expired bond
 	///   - 1 write for `coin_supply` OR read shares and execute `hand_out_coins` which has DB accesses
+	///     + read and write `CumulativeSeigniorage`
 	fn expand_supply(coin_supply: Coins, amount: Coins) -> DispatchResult {
-		// Checking whether the supply will overflow.
-		coin_supply
-			.checked_add(amount)
-			.ok_or(Error::<T>::CoinSupplyOverflow)?;
+		Self::checked_supply_increase(coin_supply, amount)?;
+		let ceiling = T::MaxCoinSupply::get().saturating_sub(coin_supply);
+		if amount > ceiling {
+			native::warn!("expansion by {} exceeds the supply ceiling --> capping at {}", amount, ceiling);
+		}
+		let amount = min(amount, ceiling);
 		// ↑ verify ↑
 		let mut remaining = amount;
-		let mut bonds = Self::bonds_transient();
+		let mut credited: Coins = 0;
+		// The refunds of expired bonds come on top of the expansion, so they are limited to the
+		// supply that is left below the ceiling after the (capped) expansion.
+		// cannot underflow because `amount` is capped at `ceiling`
+		let mut refund_headroom = ceiling - amount;
 		// ↓ update ↓
-		while let Some(Bond {
-			account,
-			payout,
-			expiration,
-		}) = if remaining > 0 { bonds.pop_front() } else { None }
-		{
-			// bond has expired --> discard
-			if <system::Module<T>>::block_number() >= expiration {
-				Self::deposit_event(RawEvent::BondExpired(account, payout));
+		if T::BondPayoutMode::get() == BondPayoutMode::ProRata {
+			let payed = Self::pay_out_bonds_pro_rata(remaining);
+			remaining -= payed;
+			credited = credited.saturating_add(payed);
+		}
+		// pays out the rest in order (all bonds if the amount covers them)
+		let mut refunded: Coins = 0;
+		// expired bonds whose refund does not fit below the ceiling, in queue order
+		let mut deferred = Vec::new();
+		let mut bonds = Self::bonds_transient();
+		while let Some(mut bond) = if remaining > 0 { bonds.pop_front() } else { None } {
+			// bond has expired --> discard and refund
+			if <system::Module<T>>::block_number() >= bond.expiration {
+				if bond.paid > refund_headroom {
+					native::warn!("refunding expired bond would exceed the supply ceiling --> deferring");
+					deferred.push(bond);
+					continue;
+				}
+				refund_headroom -= bond.paid;
+				let (account, payout) = (bond.account.clone(), bond.payout);
+				refunded += Self::refund_expired_bond(bond);
+				// `remaining` is not zero inside the loop and the routed amount is at most `remaining`
+				let routed = Self::route_expired_payout(&account, payout, remaining);
+				remaining -= routed;
+				credited = credited.saturating_add(routed);
 				continue;
 			}
 			// bond does not cover the remaining amount --> resolve and continue
-			if payout <= remaining {
+			if bond.payout <= remaining {
 				// this is safe because we are in the branch where remaining >= payout
-				remaining -= payout;
-				Self::add_balance(&account, payout);
-				Self::deposit_event(RawEvent::BondFulfilled(account, payout));
+				remaining -= bond.payout;
+				Self::pay_out_bond(&bond.account, bond.payout);
+				credited = credited.saturating_add(bond.payout);
+				Self::deposit_event(RawEvent::BondFulfilled(bond.account, bond.payout));
 			}
 			// bond covers the remaining amount --> update and finish up
 			else {
-				// this is safe because we are in the else branch where payout > remaining
-				let payout = payout - remaining;
-				Self::add_balance(&account, remaining);
-				bonds.push_front(Bond {
-					account: account.clone(),
-					payout,
-					expiration,
-				});
-				Self::deposit_event(RawEvent::BondPartiallyFulfilled(account, payout));
+				Self::pay_out_bond(&bond.account, remaining);
+				credited = credited.saturating_add(remaining);
+				bond.reduce_payout(remaining);
+				remaining = 0;
+				Self::deposit_event(RawEvent::BondPartiallyFulfilled(bond.account.clone(), bond.payout));
+				bonds.push_front(bond);
 				break;
 			}
 		}
-		// safe to do this late because of the test in the first line of the function
+		// The deferred bonds expired before all remaining bonds, so putting them back at the front
+		// keeps the queue ordered by expiration.
+		for bond in deferred.into_iter().rev() {
+			bonds.push_front(bond);
+		}
+		// the refunds are not part of the expansion
+		// cannot overflow because the refunds are limited by `refund_headroom`
+		let coin_supply = coin_supply + refunded;
+		debug_assert!(
+			credited == amount - remaining,
+			"bond holders should be credited exactly the amount payed out"
+		);
+		#[cfg(feature = "paranoid")]
+		assert!(
+			credited == amount - remaining,
+			"bond holders should be credited exactly the amount payed out"
+		);
+		// cannot overflow because of `checked_supply_increase` and the `refund_headroom`
 		// safe to subtract remaining because we initialize it with amount and never increase it
 		let new_supply = coin_supply + amount - remaining;
 		native::info!("expanded supply by paying out bonds: {}", amount - remaining);
-		if remaining > 0 {
+		if remaining > 0 && !T::SeigniorageToShareholders::get() {
+			native::info!("seigniorage to shareholders is disabled --> not creating {} coins", remaining);
+			<CoinSupply>::put(new_supply);
+			Self::deposit_event(RawEvent::ExpandedSupply(amount - remaining));
+			return Ok(());
+		}
+		let shares = Self::shares();
+		if remaining > 0 && shares.iter().all(|(_a, s)| s.is_zero()) {
+			match T::SeigniorageFallback::get() {
+				Some(fallback) => {
+					native::warn!("share supply is zero --> handing out {} coins to fallback", remaining);
+					Self::add_balance(&fallback, remaining);
+					// safe because `new_supply + remaining == coin_supply + amount` (see above)
+					<CoinSupply>::put(new_supply + remaining);
+				}
+				None => {
+					native::warn!("share supply is zero --> not creating {} coins", remaining);
+					<CoinSupply>::put(new_supply);
+					Self::deposit_event(RawEvent::ExpandedSupply(amount - remaining));
+					return Ok(());
+				}
+			}
+		} else if remaining > 0 {
 			// relies on supply being updated in `hand_out_coins`
-			Self::hand_out_coins(&Self::shares(), remaining, new_supply)
-				.expect("coin supply overflow was checked at the beginning of function; qed");
+			Self::hand_out_coins(&shares, remaining, new_supply).map_err(|e| {
+				// the bonds have been payed out already so the supply needs to reflect that
+				<CoinSupply>::put(new_supply);
+				e
+			})?;
+			<CumulativeSeigniorage>::mutate(|total| *total = total.saturating_add(remaining));
+			Self::deposit_event(RawEvent::SeigniorageDistributed(remaining));
 		} else {
 			<CoinSupply>::put(new_supply);
 		}
+		debug_assert!(
+			Self::coin_supply() == coin_supply + amount,
+			"coin supply should increase by exactly the expanded amount"
+		);
+		#[cfg(feature = "paranoid")]
+		assert!(
+			Self::coin_supply() == coin_supply + amount,
+			"coin supply should increase by exactly the expanded amount"
+		);
 		Self::deposit_event(RawEvent::ExpandedSupply(amount));
 		Ok(())
 	}
 
+	/// Sum up the shares of all `shares`.
+	///
+	/// Returns `ShareSupplyOverflow` if the sum does not fit into `u64`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(S)` with `S` being the number of shareholders
+	/// - DB access: none
+	fn checked_share_supply(shares: &[(T::AccountId, u64)]) -> Result<u64, DispatchError> {
+		shares
+			.iter()
+			.try_fold(0u64, |supply, (_a, s)| supply.checked_add(*s))
+			.ok_or_else(|| DispatchError::from(Error::<T>::ShareSupplyOverflow))
+	}
+
+	/// Return the coin supply after increasing `coin_supply` by `amount`.
+	///
+	/// Returns `CoinSupplyOverflow` if the supply would overflow. Balances are never greater than
+	/// the supply, so crediting the increase to accounts cannot overflow their balances either.
+	fn checked_supply_increase(coin_supply: Coins, amount: Coins) -> Result<Coins, DispatchError> {
+		coin_supply.checked_add(amount).ok_or_else(|| {
+			native::error!("increasing the supply {} by {} would overflow", coin_supply, amount);
+			DispatchError::from(Error::<T>::CoinSupplyOverflow)
+		})
+	}
+
+	/// Pay out `amount` proportionally to all live bonds if it does not cover all of them.
+	///
+	/// Returns the amount of Coins payed out. This is less than `amount` because of rounding and
+	/// zero if `amount` covers all live bonds (which are then payed out in order by the caller).
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bonds
+	/// - DB access:
+	///   - read bonds queue bounds + 2 reads per bond
+	///   - 1 write and the writes of `pay_out_bond` per live bond
+	fn pay_out_bonds_pro_rata(amount: Coins) -> Coins {
+		let now = <system::Module<T>>::block_number();
+		let total = Self::bonds()
+			.filter(|bond| now < bond.expiration)
+			.fold(0u128, |total, bond| total + bond.payout as u128);
+		if amount as u128 >= total {
+			return 0;
+		}
+		let (start, length) = Self::bonds_range();
+		let mut payed: Coins = 0;
+		for i in 0..length {
+			let index = start.wrapping_add(i);
+			let mut bond = Self::get_bond(index);
+			if now >= bond.expiration {
+				continue;
+			}
+			// Less than `bond.payout` because `amount < total`, fits into `Coins` for the same reason.
+			let share = (bond.payout as u128 * amount as u128 / total) as Coins;
+			if share.is_zero() {
+				continue;
+			}
+			bond.reduce_payout(share);
+			Self::pay_out_bond(&bond.account, share);
+			payed += share;
+			Self::deposit_event(RawEvent::BondPartiallyFulfilled(bond.account.clone(), bond.payout));
+			<Bonds<T>>::insert(index, bond);
+		}
+		payed
+	}
+
 	/// Hand out Coins to shareholders according to their number of shares.
 	///
 	/// Will hand out more Coins to shareholders at the beginning of the list
 	/// if the handout cannot be equal.
 	///
+	/// Shareholders with a balance below `MinSeigniorageBalance` are skipped and their part
+	/// of the handout goes to the other shareholders. If no shareholder holds the minimum
+	/// balance (e.g., on initialization), all shareholders are considered.
+	///
+	/// No shareholder receives more than `MaxShareOfExpansion` of `amount` unless there are
+	/// too few shareholders to hand out the whole amount that way.
+	///
+	/// Returns `ZeroShareSupply` if `shares` is empty or all share counts are zero
+	/// as the Coins cannot be split among the shareholders in that case.
+	/// Returns `SupplyCeilingReached` if the supply would exceed `MaxCoinSupply`.
+	///
 	/// **Weight:**
-	/// - complexity: `O(S + C)`
-	///   - `S` being `shares.len()` (the number of shareholders)
+	/// - complexity: `O(S^2 + C)`
+	///   - `S` being `shares.len()` (the number of shareholders), squared for `cap_payouts`
 	///   - `C` being a constant amount of storage reads and writes for coin supply
 	/// - DB access:
 	///   - 1 write for `coin_supply`
+	///   - `S` amount of reads if `MinSeigniorageBalance` is greater than zero
 	///   - `S` amount of writes
 	fn hand_out_coins(shares: &[(T::AccountId, u64)], amount: Coins, coin_supply: Coins) -> DispatchResult {
-		// Checking whether the supply will overflow.
-		coin_supply
-			.checked_add(amount)
-			.ok_or(Error::<T>::CoinSupplyOverflow)?;
+		let new_supply = Self::checked_supply_increase(coin_supply, amount)?;
+		ensure!(new_supply <= T::MaxCoinSupply::get(), Error::<T>::SupplyCeilingReached);
+		let min_balance = T::MinSeigniorageBalance::get();
+		let qualifying: Vec<(T::AccountId, u64)>;
+		let shares = if min_balance.is_zero() {
+			shares
+		} else {
+			qualifying = shares
+				.iter()
+				.filter(|(acc, _s)| Self::get_balance(acc) >= min_balance)
+				.cloned()
+				.collect();
+			if qualifying.is_empty() {
+				shares
+			} else {
+				&qualifying[..]
+			}
+		};
+		let share_supply = Self::checked_share_supply(shares)?;
+		if share_supply.is_zero() {
+			native::error!("share supply is zero --> cannot hand out coins");
+			return Err(DispatchError::from(Error::<T>::ZeroShareSupply));
+		}
 		// ↑ verify ↑
-		let share_supply: u64 = shares.iter().map(|(_a, s)| s).sum();
 		let len = shares.len() as u64;
-		// No point in giving out less than 1 coin.
-		let coins_per_share = max(1, amount / share_supply);
-		let pay_extra = coins_per_share * len < amount;
-		let mut amount_payed = 0;
-		// ↓ update ↓
-		for (i, (acc, num_shares)) in shares.iter().enumerate() {
-			if amount_payed >= amount {
-				break;
-			}
-			let max_payout = amount - amount_payed;
-			let is_in_first_mod_len = (i as u64) < amount % len;
-			let extra_payout = if pay_extra && is_in_first_mod_len { 1 } else { 0 };
-			let payout = min(max_payout, num_shares * coins_per_share + extra_payout);
-			debug_assert!(
-				amount_payed + payout <= amount,
-				"amount payed out should be less or equal target amount"
-			);
-			Self::add_balance(&acc, payout);
-			amount_payed += payout;
+		// cannot overflow because `num_shares <= share_supply`
+		let mut payouts: Vec<Coins> = shares
+			.iter()
+			.map(|(_acc, num_shares)| (*num_shares as u128 * amount as u128 / share_supply as u128) as Coins)
+			.collect();
+		// rounding down loses less than 1 coin per shareholder --> hand it out in order
+		let remainder = amount - payouts.iter().sum::<Coins>();
+		debug_assert!(remainder < len, "the rounding remainder should be less than the shareholders");
+		for payout in payouts.iter_mut().take(remainder as usize) {
+			*payout += 1;
 		}
 		debug_assert!(
-			amount_payed == amount,
+			payouts.iter().sum::<Coins>() == amount,
 			"amount payed out should equal target amount"
 		);
+		let max_payout = T::MaxShareOfExpansion::get() * amount;
+		// the cap can only be applied if the shareholders can take the whole amount together
+		if max_payout.saturating_mul(len) >= amount {
+			let weights: Vec<u64> = shares.iter().map(|(_a, s)| *s).collect();
+			Self::cap_payouts(&mut payouts, &weights, max_payout);
+		}
+		// ↓ update ↓
+		for ((acc, _s), payout) in shares.iter().zip(payouts) {
+			if payout > 0 {
+				Self::add_balance(&acc, payout);
+			}
+		}
 
-		// safe to do this late because of the test in the first line of the function
-		let new_supply = coin_supply + amount;
+		// checked by `checked_supply_increase` in the first line of the function
 		<CoinSupply>::put(new_supply);
 		native::info!("expanded supply by handing out coins: {}", amount);
 		Ok(())
 	}
 
+	/// Limit each of the `payouts` to `max_payout` and hand out the excess to the
+	/// other payouts according to their `weights` until no payout exceeds the maximum.
+	///
+	/// Expects `max_payout * payouts.len()` to be at least the sum of the `payouts`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(P^2)` with `P` being `payouts.len()` (the number of shareholders)
+	/// - DB access: none
+	fn cap_payouts(payouts: &mut [Coins], weights: &[u64], max_payout: Coins) {
+		loop {
+			let excess = payouts
+				.iter()
+				.fold(0, |total: Coins, p| total.saturating_add(p.saturating_sub(max_payout)));
+			if excess.is_zero() {
+				break;
+			}
+			for payout in payouts.iter_mut() {
+				*payout = min(*payout, max_payout);
+			}
+			let open: Vec<usize> = (0..payouts.len()).filter(|i| payouts[*i] < max_payout).collect();
+			if open.is_empty() {
+				native::error!("unable to cap payouts --> dropping excess of {}", excess);
+				break;
+			}
+			let open_weight: u128 = open.iter().map(|i| weights[*i] as u128).sum();
+			let mut distributed = 0;
+			for i in open.iter() {
+				let extra = if open_weight.is_zero() {
+					excess / open.len() as u64
+				} else {
+					// cannot overflow because `weights[*i] <= open_weight`
+					(excess as u128 * weights[*i] as u128 / open_weight) as u64
+				};
+				payouts[*i] += extra;
+				distributed += extra;
+			}
+			// hand out the rounding remainder (less than `open.len()`) one coin at a time
+			for i in open.iter().take((excess - distributed) as usize) {
+				payouts[*i] += 1;
+			}
+		}
+	}
+
+	// ------------------------------------------------------------
+	// queries
+
+	/// Return the number of bids in the bidding queue.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn bid_count() -> u32 {
+		Self::bond_bids().len() as u32
+	}
+
+	/// Return the price of the best bid (the one filled first), `None` if there are no bids.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn best_bid_price() -> Option<Perbill> {
+		// the best bid is stored last to be popped first
+		Self::bond_bids().last().map(|bid| bid.price)
+	}
+
+	/// Return the total amount of Coins offered by all bids.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn total_bid_demand() -> Coins {
+		Self::bond_bids()
+			.iter()
+			.fold(0, |total: Coins, bid| total.saturating_add(bid.payment()))
+	}
+
+	/// Return the fraction of a hypothetical bid for `quantity` Coins at `price` that would be
+	/// filled by a contraction of `expected_contraction` Coins with the current bids.
+	///
+	/// Like a new bid, the hypothetical bid is filled after the bids with the same price.
+	/// Does not take the eviction from a full bid queue or the `MaxBonds` limit into account.
+	/// Returns zero if the bid would not pay anything (e.g., for a zero quantity).
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn fill_fraction(price: Perbill, quantity: Coins, expected_contraction: Coins) -> Perbill {
+		let payment = price * quantity;
+		if payment.is_zero() {
+			return Perbill::zero();
+		}
+		let filled_first = Self::bond_bids()
+			.iter()
+			.filter(|bid| bid.price >= price)
+			.fold(0, |total: Coins, bid| total.saturating_add(bid.payment()));
+		let available = expected_contraction.saturating_sub(filled_first);
+		Perbill::from_rational_approximation(min(available, payment), payment)
+	}
+
+	/// Return the number of bonds in the bond queue (including expired ones not yet removed).
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: read bonds queue bounds
+	pub fn bond_count() -> BondIndex {
+		Self::bonds_range().1
+	}
+
+	/// Return statistics of the payouts of the bonds in the bond queue (including expired ones not
+	/// yet removed) as `(min, max, total, count)`.
+	///
+	/// Returns all zeros if there are no bonds.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bonds
+	/// - DB access: read bonds queue bounds + 1 read per bond
+	pub fn bond_stats() -> (Coins, Coins, Coins, u32) {
+		Self::bonds().fold((0, 0, 0, 0), |(min_payout, max_payout, total, count), bond| {
+			let min_payout = if count == 0 { bond.payout } else { min(min_payout, bond.payout) };
+			(
+				min_payout,
+				max(max_payout, bond.payout),
+				total.saturating_add(bond.payout),
+				count + 1,
+			)
+		})
+	}
+
+	/// Return the current price reported by the oracle and the base unit as `(price, base_unit)`.
+	///
+	/// The price is on target if both are equal. Reads the price with `CoinPrice::fetch_price`, so
+	/// no price is recorded (e.g., into the `PriceWindow` of `MovingAverage`).
+	///
+	/// **Weight:**
+	/// - complexity: `O(P)` with `P` being the complexity of `CoinPrice::fetch_price`
+	/// - DB access: the reads of `CoinPrice::fetch_price` + 1 read for the base unit override
+	pub fn current_peg_ratio() -> (Coins, Coins) {
+		(T::CoinPrice::fetch_price(), Self::base_unit())
+	}
+
+	/// Return the headline state of the stablecoin in a single query.
+	///
+	/// Like the individual queries it does not change any state.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B + S + P)`
+	///   - `B` being the number of bonds and bids
+	///   - `S` being the number of shareholders
+	///   - `P` being the complexity of `CoinPrice::fetch_price`
+	/// - DB access: the reads of the individual queries
+	pub fn overview() -> StablecoinOverview<T::BlockNumber> {
+		let (_min, _max, outstanding_bond_liability, _count) = Self::bond_stats();
+		let shares = Self::shares();
+		StablecoinOverview {
+			coin_supply: Self::coin_supply(),
+			share_supply: shares
+				.iter()
+				.fold(0, |total: u64, (_acc, shares)| total.saturating_add(*shares)),
+			shareholder_count: shares.len() as u32,
+			bid_count: Self::bid_count(),
+			bond_count: Self::bond_count(),
+			outstanding_bond_liability,
+			current_peg_ratio: Self::current_peg_ratio(),
+			last_rebase: Self::last_rebase(),
+		}
+	}
+
+	/// Return the supply change needed to restore the peg at the current price without changing
+	/// the supply.
+	///
+	/// This is the change a rebase would request at this price. A contraction could burn less if
+	/// there are not enough bids. Returns `Expand(0)` if the price is zero, within the target band
+	/// or outside of `MinAcceptablePrice` and `MaxAcceptablePrice` (the rebase is skipped then).
+	/// Like `current_peg_ratio` it does not record a price.
+	///
+	/// **Weight:**
+	/// - complexity: `O(P)` with `P` being the complexity of `CoinPrice::fetch_price`
+	/// - DB access: the reads of `CoinPrice::fetch_price` + the reads of `supply_change_on_price`
+	pub fn coins_to_peg() -> SupplyAction {
+		match T::CoinPrice::fetch_price() {
+			0 => SupplyAction::default(),
+			price if !Self::is_acceptable_price(price) => SupplyAction::default(),
+			price => Self::supply_change_on_price(price),
+		}
+	}
+
+	/// Return the balances of all accounts (e.g., for snapshots).
+	///
+	/// Note: Iterates over all accounts, meant for off-chain use (e.g., via the runtime API) and
+	/// not for use in a block.
+	///
+	/// **Weight:**
+	/// - complexity: `O(A)` with `A` being the number of accounts with a balance
+	/// - DB access: `A` storage map reads
+	pub fn all_balances() -> Vec<(T::AccountId, Coins)> {
+		<Balance<T>>::iter().collect()
+	}
+
+	/// Return the amount of Coins in circulation, including the Coins locked in bids.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 read
+	pub fn circulating_supply() -> Coins {
+		Self::coin_supply()
+	}
+
+	/// Check whether the coin supply equals the sum of all balances plus the Coins locked in bids.
+	///
+	/// Note: Iterates over all accounts, only meant for checking the supply accounting in
+	/// debug builds.
+	///
+	/// **Weight:**
+	/// - complexity: `O(A)` with `A` being the number of accounts with a balance
+	/// - DB access: `A` storage map reads + 2 reads for coin supply and bid escrow
+	#[cfg(debug_assertions)]
+	pub fn check_supply_invariant() -> bool {
+		let balances = <Balance<T>>::iter().fold(0u128, |total, (_acc, balance)| total + balance as u128);
+		balances + Self::bid_escrow() as u128 == Self::coin_supply() as u128
+	}
+
+	/// Return the total value of `who` in Coins.
+	///
+	/// Sums up the balance, the payouts of all unexpired bonds held and the Coins locked in bids.
+	///
+	/// **Weight:**
+	/// - complexity: `O(BO + BI)`
+	///   - `BO` being the number of bonds
+	///   - `BI` being the number of bids in the bidding auction, limited to `MaximumBids`
+	/// - DB access: 1 storage map read + read bonds + read bids
+	pub fn account_total_value(who: &T::AccountId) -> Coins {
+		Self::get_balance(who)
+			.saturating_add(Self::bond_payouts_of(who))
+			.saturating_add(Self::bid_collateral_of(who))
+	}
+
+	/// Return the bids that would be filled if the supply was contracted by `amount` right now.
+	///
+	/// Each fill is returned as `(account, price_paid, bond_payout)` in the order in which the
+	/// bids would be filled. The `bond_payout` is the total payout of the bonds created for the
+	/// fill (which may be split at `MaximumBondSize`). Uses the same matching as `contract_supply`
+	/// (see `match_bids`) without changing storage and without checking the coin supply limits or
+	/// burning from the `ContractionReserve`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids + read bonds queue bounds
+	pub fn simulate_contraction(amount: Coins) -> Vec<(T::AccountId, Coins, Coins)> {
+		let mut bids = Self::bond_bids();
+		let (matches, _remaining) = Self::match_bids(&mut bids, amount);
+		matches
+			.into_iter()
+			.filter_map(|bid_match| match bid_match {
+				BidMatch::Filled(bid, payment, payout) => Some((bid.account, payment, payout)),
+				// a bid that cannot be reduced would be refunded instead of filled
+				BidMatch::Unfillable(_bid) => None,
+			})
+			.collect()
+	}
+
+	/// Return the price and the bond payout of the last bid that would be (partially) filled
+	/// if the supply was contracted by `amount` right now (i.e., the clearing price).
+	///
+	/// Uses the same matching as `contract_supply` (see `match_bids`). Returns `None` if `amount`
+	/// is zero or the bids cannot cover the contraction (e.g., because of `MaxBonds`).
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids + read bonds queue bounds
+	pub fn marginal_bid(amount: Coins) -> Option<(Perbill, Coins)> {
+		let mut bids = Self::bond_bids();
+		let (matches, remaining) = Self::match_bids(&mut bids, amount);
+		if remaining > 0 {
+			return None;
+		}
+		matches.into_iter().rev().find_map(|bid_match| match bid_match {
+			BidMatch::Filled(bid, _payment, payout) => Some((bid.price, payout)),
+			BidMatch::Unfillable(_bid) => None,
+		})
+	}
+
+	/// Return the recorded rebases in the blocks from `from` to `to` (inclusive), oldest first.
+	///
+	/// Only the last `u8::max_value()` rebases are recorded.
+	///
+	/// **Weight:**
+	/// - complexity: `O(H)` with `H` being the length of the history, limited to `u8::max_value()`
+	/// - DB access: read history bounds + `H` history reads
+	pub fn rebases_in_range(from: T::BlockNumber, to: T::BlockNumber) -> Vec<(T::BlockNumber, SupplyAction)> {
+		let (start, length) = Self::rebase_history_range();
+		(0..length)
+			.map(|i| Self::get_rebase(start.wrapping_add(i)))
+			.filter(|(block, _action)| from <= *block && *block <= to)
+			.collect()
+	}
+
+	/// Return whether `who` can place a bid for `quantity` Coins at `price`.
+	///
+	/// Checks the bid against the configured limits and the payment against the balance of `who`.
+	/// Coins locked in existing bids are not part of the balance and thus not available.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 1 storage map read
+	pub fn can_bid(who: &T::AccountId, price: Perbill, quantity: Coins) -> bool {
+		Self::ensure_valid_bid(price, quantity).is_ok() && Self::get_balance(who) >= price * quantity
+	}
+
+	/// Return the sum of the payouts of all unexpired bonds held by `who`.
+	fn bond_payouts_of(who: &T::AccountId) -> Coins {
+		let now = <system::Module<T>>::block_number();
+		Self::bonds()
+			.filter(|bond| &bond.account == who && bond.expiration > now)
+			.fold(0, |total: Coins, bond| total.saturating_add(bond.payout))
+	}
+
+	/// Return the bids of `who` including the block each bid was placed in.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn bids_of(who: &T::AccountId) -> Vec<Bid<T::AccountId, T::BlockNumber>> {
+		Self::bond_bids()
+			.into_iter()
+			.filter(|bid| &bid.account == who)
+			.collect()
+	}
+
+	/// Return the bonds of `who` in the order of the bond queue (including expired ones not yet
+	/// removed).
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bonds
+	/// - DB access: read bonds queue bounds + `B` bond reads
+	pub fn bonds_of(who: &T::AccountId) -> Vec<Bond<T::AccountId, T::BlockNumber>> {
+		Self::bonds().filter(|bond| &bond.account == who).collect()
+	}
+
+	/// Return the bids of `who` together with their position in the bidding queue,
+	/// ordered by position.
+	///
+	/// Position 0 is the bid that is filled first in the next contraction.
+	///
+	/// **Weight:**
+	/// - complexity: `O(B)` with `B` being the number of bids, limited to `MaximumBids`
+	/// - DB access: read bids
+	pub fn my_bid_priorities(who: &T::AccountId) -> Vec<(u32, Bid<T::AccountId, T::BlockNumber>)> {
+		// the bids are sorted in ascending order and the greatest is filled first
+		Self::bond_bids()
+			.into_iter()
+			.rev()
+			.enumerate()
+			.filter(|(_pos, bid)| &bid.account == who)
+			.map(|(pos, bid)| (pos as u32, bid))
+			.collect()
+	}
+
+	/// Return the amount of Coins locked in the bids of `who`.
+	fn bid_collateral_of(who: &T::AccountId) -> Coins {
+		Self::bids_of(who)
+			.iter()
+			.fold(0, |total: Coins, bid| total.saturating_add(bid.payment()))
+	}
+
 	// ------------------------------------------------------------
 	// on block
 
+	/// Return the weight of `on_initialize` for block `block`.
+	///
+	/// Blocks that adjust the supply may touch every bond and bid in the queues, so their weight
+	/// grows with the queue lengths. The lengths are decoded without loading the queues.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: read bonds queue bounds + decode length of bids
+	pub fn on_initialize_weight(block: T::BlockNumber) -> Weight {
+		if block % T::AdjustmentFrequency::get() != 0.into() {
+			return PRICE_CHECK_WEIGHT;
+		}
+		Self::rebase_weight()
+	}
+
+	/// Return the weight of fetching the price and adjusting the supply with the current queues.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: read bonds queue bounds + decode length of bids
+	pub fn rebase_weight() -> Weight {
+		let (_start, bonds) = Self::bonds_range();
+		let bids = <BondBids<T>>::decode_len().unwrap_or(0);
+		let items = (bonds as Weight).saturating_add(bids as Weight);
+		QUEUE_ITEM_WEIGHT.saturating_mul(items).saturating_add(PRICE_CHECK_WEIGHT)
+	}
+
 	/// Contracts or expands the supply based on conditions.
 	///
 	/// **Weight:**
-	/// Calls `expand_or_contract_on_price` every `AdjustmentFrequency` blocks.
-	/// - complexity: `O(P)` with `P` being the complexity of `expand_or_contract_on_price`
+	/// Calls `rebase_on_price` every `AdjustmentFrequency` blocks.
+	/// - complexity: `O(P)` with `P` being the complexity of `rebase_on_price`
 	fn on_block_with_price(block: T::BlockNumber, price: Coins) -> DispatchResult {
 		// This can be changed to only correct for small or big price swings.
 		if block % T::AdjustmentFrequency::get() == 0.into() {
-			Self::expand_or_contract_on_price(price)
+			Self::rebase_on_price(block, price)
 		} else {
 			Ok(())
 		}
 	}
 
-	/// Expands (if the price is too high) or contracts (if the price is too low) the coin supply.
+	/// Rebase the supply at `price` in `block`, used by `on_block_with_price` and `poke`.
+	///
+	/// Records the block and amount in `LastRebase` and the rebase history if the supply actually changed.
+	///
+	/// Skips the rebase if the price is outside of `MinAcceptablePrice` and `MaxAcceptablePrice`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(P)` with `P` being the complexity of `expand_or_contract_on_price`
+	/// - DB access:
+	///   - 2 reads for coin supply
+	///   - potentially 1 write for `LastRebase` + 1 history write + read and write history bounds
+	fn rebase_on_price(block: T::BlockNumber, price: Coins) -> DispatchResult {
+		if !Self::is_acceptable_price(price) {
+			native::warn!("price {} is out of the acceptable range --> skipping rebase", price);
+			Self::deposit_event(RawEvent::PriceOutOfBounds(price));
+			return Ok(());
+		}
+		let supply = Self::coin_supply();
+		Self::expand_or_contract_on_price(price)?;
+		let new_supply = Self::coin_supply();
+		let action = if new_supply > supply {
+			SupplyAction::Expand(new_supply - supply)
+		} else if new_supply < supply {
+			SupplyAction::Contract(supply - new_supply)
+		} else {
+			return Ok(());
+		};
+		<LastRebase<T>>::put((block, action.clone()));
+		Self::rebase_history_transient().push_back((block, action));
+		Ok(())
+	}
+
+	/// Return whether `price` is within `MinAcceptablePrice` and `MaxAcceptablePrice`.
+	///
+	/// Readings outside of the bounds are not used for rebases.
+	fn is_acceptable_price(price: Coins) -> bool {
+		price >= T::MinAcceptablePrice::get() && price <= T::MaxAcceptablePrice::get()
+	}
+
+	/// Expands (if the price is too low) or contracts (if the price is too high) the coin supply.
+	///
+	/// The price is compared against the `target_band` and the supply change is computed from the
+	/// deviation to the nearer end of the band.
+	///
+	/// If `ContractionCarryover` is enabled the part of a contraction that could not be covered
+	/// by bids is added to the next contraction (as far as the supply can be contracted without
+	/// going below `MinimumSupply`). The carryover is discarded once the price is at or below the
+	/// target.
 	///
 	/// **Weight:**
 	/// - complexity: `O(S + C)`
@@ -961,34 +3268,84 @@ impl<T: Trait> Module<T> {
 	///   - 1 read for coin_supply
 	///   - execute `expand_supply` OR execute `contract_supply` which have DB accesses
 	fn expand_or_contract_on_price(price: Coins) -> DispatchResult {
-		match price {
-			0 => {
-				native::error!("coin price is zero!");
-				return Err(DispatchError::from(Error::<T>::ZeroPrice));
-			}
-			price if price > T::BaseUnit::get() => {
-				// safe from underflow because `price` is checked to be greater than `BaseUnit`
-				let supply = Self::coin_supply();
-				let contract_by = Self::calculate_supply_change(price, T::BaseUnit::get(), supply);
-				Self::contract_supply(supply, contract_by)?;
-			}
-			price if price < T::BaseUnit::get() => {
-				// safe from underflow because `price` is checked to be less than `BaseUnit`
-				let supply = Self::coin_supply();
-				let expand_by = Self::calculate_supply_change(T::BaseUnit::get(), price, supply);
-				Self::expand_supply(supply, expand_by)?;
+		if price == 0 {
+			native::error!("coin price is zero!");
+			return Err(DispatchError::from(Error::<T>::ZeroPrice));
+		}
+		let supply = Self::coin_supply();
+		match Self::supply_change_on_price(price) {
+			SupplyAction::Contract(contract_by) => {
+				let burned = Self::contract_supply(supply, contract_by)?;
+				if T::ContractionCarryover::get() {
+					<PendingContraction>::put(contract_by - burned);
+				} else {
+					<PendingContraction>::kill();
+				}
 			}
-			_ => {
-				native::info!("coin price is equal to base as is desired --> nothing to do");
+			SupplyAction::Expand(0) => {
+				native::info!("coin price is within the target band as is desired --> nothing to do");
+				<PendingContraction>::kill();
+			}
+			SupplyAction::Expand(expand_by) => {
+				<PendingContraction>::kill();
+				Self::expand_supply(supply, expand_by)?;
 			}
 		}
 		Ok(())
 	}
 
+	/// Calculate the supply change that `expand_or_contract_on_price` would request for the
+	/// non-zero `price`. Includes the `PendingContraction` for contractions, capped so that the
+	/// contraction does not take the supply below `MinimumSupply`.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: 4 reads for target band, coin supply and pending contraction
+	fn supply_change_on_price(price: Coins) -> SupplyAction {
+		let supply = Self::coin_supply();
+		match Self::compute_supply_delta(price, supply, Self::target_band()) {
+			SupplyAction::Contract(contract_by) => {
+				// Capping the carryover keeps it from growing every block while there are no bids.
+				let contractable = supply.saturating_sub(T::MinimumSupply::get());
+				let pending = min(
+					Self::pending_contraction(),
+					contractable.saturating_sub(contract_by),
+				);
+				SupplyAction::Contract(contract_by.saturating_add(pending))
+			}
+			expand => expand,
+		}
+	}
+
+	/// Calculate the supply change needed to bring the non-zero `price` back into the target
+	/// `band` (given as `(low, high)`) for the given `supply`.
+	///
+	/// The change is computed from the deviation to the nearer end of the band. Returns
+	/// `Expand(0)` if the price is within the band.
+	///
+	/// **Weight:**
+	/// - complexity: `O(1)`
+	/// - DB access: none
+	fn compute_supply_delta(price: Coins, supply: Coins, band: (Coins, Coins)) -> SupplyAction {
+		debug_assert!(price > 0, "price needs to be non-zero to compute the supply delta");
+		let (low, high) = band;
+		if price > high {
+			// safe from underflow because `price` is checked to be greater than `high`
+			SupplyAction::Contract(Self::calculate_supply_change(price, high, supply))
+		} else if price < low {
+			// safe from underflow because `price` is checked to be less than `low`
+			SupplyAction::Expand(Self::calculate_supply_change(low, price, supply))
+		} else {
+			SupplyAction::Expand(0)
+		}
+	}
+
 	/// Calculate the amount of supply change from a fraction given as `numerator` and `denominator`.
+	///
+	/// Saturates at `u64::max_value()` for extreme fractions (e.g., a price far above the target).
 	fn calculate_supply_change(numerator: u64, denominator: u64, supply: u64) -> u64 {
 		type Fix = FixedU128<U64>;
 		let fraction = Fix::from_num(numerator) / Fix::from_num(denominator) - Fix::from_num(1);
-		fraction.saturating_mul_int(supply as u128).to_num::<u64>()
+		fraction.saturating_mul_int(supply as u128).saturating_to_num::<u64>()
 	}
 }
@@ -0,0 +1,104 @@
+//! # Stablecoin Runtime API
+//!
+//! Read-only queries into the stablecoin state meant to be called by clients (e.g., wallets)
+//! through the node's RPC instead of decoding the raw storage.
+//!
+//! ## Registration
+//!
+//! The runtime implements the API by forwarding to the pallet inside `impl_runtime_apis!`:
+//!
+//! ```rust,ignore
+//! impl_runtime_apis! {
+//!     // --snip--
+//!     impl pallet_stablecoin::runtime_api::StablecoinApi<Block, AccountId, BlockNumber> for Runtime {
+//!         fn coin_supply() -> Coins {
+//!             Stablecoin::coin_supply()
+//!         }
+//!         // ... the other methods
+//!     }
+//! }
+//! ```
+//!
+//! The node can then query it on any client implementing `ProvideRuntimeApi`
+//! (e.g., `client.runtime_api().coin_supply(&at)`) and expose it through a custom RPC.
+
+use codec::Codec;
+use sp_runtime::Perbill;
+use sp_std::prelude::*;
+
+use crate::{Bid, BondIndex, Coins, StablecoinOverview, SupplyAction};
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the stablecoin state.
+	pub trait StablecoinApi<AccountId, BlockNumber> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Return the amount of Coins in circulation.
+		fn coin_supply() -> Coins;
+
+		/// Return the balance of `who`.
+		fn get_balance(who: AccountId) -> Coins;
+
+		/// Return the number of bids in the bidding queue.
+		fn bid_count() -> u32;
+
+		/// Return the number of bonds in the bond queue (including expired ones not yet removed).
+		fn bond_count() -> BondIndex;
+
+		/// Return the payout statistics of the bond queue as `(min, max, total, count)`.
+		fn bond_stats() -> (Coins, Coins, Coins, u32);
+
+		/// Return the current price and the base unit (the target price) as `(price, base_unit)`.
+		fn current_peg_ratio() -> (Coins, Coins);
+
+		/// Return the supply change needed to restore the peg at the current price.
+		///
+		/// Returns no change for prices outside of the acceptable bounds, as no rebase happens then.
+		fn coins_to_peg() -> SupplyAction;
+
+		/// Return the total value of `who` in Coins.
+		///
+		/// Includes the liquid balance, the payouts of all bonds held and the Coins locked in bids.
+		fn account_total_value(who: AccountId) -> Coins;
+
+		/// Return the balances of all accounts. Iterates over all accounts.
+		fn all_balances() -> Vec<(AccountId, Coins)>;
+
+		/// Return the block and the action of the last adjustment that changed the coin supply.
+		fn last_rebase() -> Option<(BlockNumber, SupplyAction)>;
+
+		/// Return the recorded rebases in the blocks from `from` to `to` (inclusive), oldest first.
+		fn rebases_in_range(from: BlockNumber, to: BlockNumber) -> Vec<(BlockNumber, SupplyAction)>;
+
+		/// Return whether `who` can afford a bid for `quantity` Coins at `price`.
+		///
+		/// Coins locked in existing bids are not available for new bids.
+		fn can_bid(who: AccountId, price: Perbill, quantity: Coins) -> bool;
+
+		/// Return the bids of `who` with their position in the bidding queue (0 being filled first).
+		fn my_bid_priorities(who: AccountId) -> Vec<(u32, Bid<AccountId, BlockNumber>)>;
+
+		/// Return the bids filled by a contraction of `amount` Coins as `(account, price_paid, bond_payout)`.
+		fn simulate_contraction(amount: Coins) -> Vec<(AccountId, Coins, Coins)>;
+
+		/// Return the price and bond payout of the last bid filled by a contraction of `amount`.
+		fn marginal_bid(amount: Coins) -> Option<(Perbill, Coins)>;
+
+		/// Return the total amount of Coins handed out to the shareholders in supply expansions.
+		fn cumulative_seigniorage() -> Coins;
+
+		/// Return the headline state of the stablecoin in a single call.
+		fn overview() -> StablecoinOverview<BlockNumber>;
+
+		/// Return the price of the best bid (the one filled first), `None` if there are no bids.
+		fn best_bid_price() -> Option<Perbill>;
+
+		/// Return the total amount of Coins offered by all bids.
+		fn total_bid_demand() -> Coins;
+
+		/// Return the fraction of a hypothetical bid for `quantity` Coins at `price` that would be
+		/// filled by a contraction of `expected_contraction` Coins with the current bids.
+		fn fill_fraction(price: Perbill, quantity: Coins, expected_contraction: Coins) -> Perbill;
+	}
+}
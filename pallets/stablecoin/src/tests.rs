@@ -3,15 +3,19 @@ use super::*;
 use itertools::Itertools;
 use log;
 use more_asserts::*;
-use quickcheck::{QuickCheck, TestResult};
-use rand::{thread_rng, Rng};
-use std::sync::atomic::{AtomicU64, Ordering};
-
-use frame_support::{assert_ok, impl_outer_origin, parameter_types, weights::Weight};
+use quickcheck::{QuickCheck, StdGen, TestResult};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use std::cell::RefCell;
+
+use frame_support::{
+	assert_noop, assert_ok, impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
+	storage::StorageMap,
+	weights::{GetDispatchInfo, Weight},
+};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, IdentityLookup, OnInitialize, OnRuntimeUpgrade},
 	Fixed64, Perbill,
 };
 use sp_std::iter;
@@ -21,21 +25,285 @@ impl_outer_origin! {
 	pub enum Origin for Test {}
 }
 
+mod stablecoin {
+	// Re-export needed for `impl_outer_event!`.
+	pub use super::super::*;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		stablecoin<T>,
+	}
+}
+
+impl_outer_dispatch! {
+	pub enum TestCall for Test where origin: Origin {
+		stablecoin::Stablecoin,
+	}
+}
+
 const TEST_BASE_UNIT: u64 = 1000;
-static LAST_PRICE: AtomicU64 = AtomicU64::new(TEST_BASE_UNIT);
+
+/// Environment variable to replay the randomness of a test with a logged seed.
+const SEED_VAR: &str = "STABLECOIN_TEST_SEED";
+
+thread_local! {
+	// Tests run in their own threads, so every test uses its own seed and price history.
+	static TEST_SEED: u64 = read_or_create_seed();
+	static PRICE_RNG: RefCell<StdRng> = RefCell::new(seeded_rng());
+	static LAST_PRICE: RefCell<Coins> = RefCell::new(TEST_BASE_UNIT);
+	static NEXT_PRICE: RefCell<Coins> = RefCell::new(TEST_BASE_UNIT);
+}
+
+/// Read the seed from `STABLECOIN_TEST_SEED` or create a random one.
+///
+/// The seed is printed, so it shows up in the captured output of a failing test.
+/// Replay the failure by passing the seed back in, e.g.
+/// `STABLECOIN_TEST_SEED=1234 cargo test -p pallet-stablecoin handout_quickcheck`.
+fn read_or_create_seed() -> u64 {
+	let seed = std::env::var(SEED_VAR)
+		.ok()
+		.and_then(|s| s.parse().ok())
+		.unwrap_or_else(|| thread_rng().gen());
+	println!("{}={}", SEED_VAR, seed);
+	seed
+}
+
+/// Return a new rng seeded with the seed of the current test.
+fn seeded_rng() -> StdRng {
+	StdRng::seed_from_u64(TEST_SEED.with(|s| *s))
+}
+
+/// Return a `QuickCheck` that generates its inputs from the seed of the current test.
+fn seeded_quickcheck() -> QuickCheck<StdGen<StdRng>> {
+	// 100 is the default size of `QuickCheck::new`
+	QuickCheck::new().gen(StdGen::new(seeded_rng(), 100))
+}
+
 pub struct RandomPrice;
 
 impl FetchPrice<Coins> for RandomPrice {
 	fn fetch_price() -> Coins {
-		let prev = LAST_PRICE.load(Ordering::SeqCst);
-		let random = thread_rng().gen_range(500, 1500);
+		PRICE_OVERRIDE
+			.with(|v| *v.borrow())
+			.unwrap_or_else(|| LAST_PRICE.with(|v| *v.borrow()))
+	}
+
+	fn record_price() {
+		if PRICE_OVERRIDE.with(|v| v.borrow().is_some()) {
+			return;
+		}
+		let prev = NEXT_PRICE.with(|v| *v.borrow());
+		let random = PRICE_RNG.with(|rng| rng.borrow_mut().gen_range(500, 1500));
 		let ratio: Ratio<u64> = Ratio::new(random, 1000);
 		let next = ratio
 			.checked_mul(&prev.into())
 			.map(|r| r.to_integer())
 			.unwrap_or(prev);
-		LAST_PRICE.store(next + 1, Ordering::SeqCst);
-		prev
+		NEXT_PRICE.with(|v| *v.borrow_mut() = next + 1);
+		LAST_PRICE.with(|v| *v.borrow_mut() = prev);
+	}
+}
+
+pub struct SpotPrice;
+
+impl FetchPrice<Coins> for SpotPrice {
+	fn fetch_price() -> Coins {
+		SPOT_PRICE.with(|v| *v.borrow())
+	}
+}
+
+pub struct EightDecimalsOracle;
+
+impl FetchPrice<u128> for EightDecimalsOracle {
+	fn fetch_price() -> u128 {
+		// 1.05
+		105_000_000
+	}
+}
+
+pub struct EighteenDecimalsOracle;
+
+impl FetchPrice<u128> for EighteenDecimalsOracle {
+	fn fetch_price() -> u128 {
+		// 0.9876543
+		987_654_300_000_000_000
+	}
+}
+
+thread_local! {
+	static CONTRACTION_CARRYOVER: RefCell<bool> = RefCell::new(false);
+	static MIN_SEIGNIORAGE_BALANCE: RefCell<Coins> = RefCell::new(0);
+	static SEIGNIORAGE_FALLBACK: RefCell<Option<AccountId>> = RefCell::new(None);
+	static MAX_BID_INTAKE_PER_BLOCK: RefCell<Coins> = RefCell::new(Coins::max_value());
+	static MAX_SHARE_OF_EXPANSION: RefCell<Perbill> = RefCell::new(Perbill::one());
+	static CONTRACTION_RESERVE: RefCell<Option<AccountId>> = RefCell::new(None);
+	static BID_COOLDOWN: RefCell<BlockNumber> = RefCell::new(0);
+	static BOND_PAYOUT_MODE: RefCell<BondPayoutMode> = RefCell::new(BondPayoutMode::Fifo);
+	static MAX_COIN_SUPPLY: RefCell<Coins> = RefCell::new(Coins::max_value());
+	static ACCEPTABLE_PRICE: RefCell<(Coins, Coins)> = RefCell::new((0, Coins::max_value()));
+	static BONDS_EXPIRE: RefCell<bool> = RefCell::new(true);
+	static MAXIMUM_BOND_SIZE: RefCell<Coins> = RefCell::new(Coins::max_value());
+	static SEIGNIORAGE_TO_SHAREHOLDERS: RefCell<bool> = RefCell::new(true);
+	static SPOT_PRICE: RefCell<Coins> = RefCell::new(TEST_BASE_UNIT);
+	static PRICE_OVERRIDE: RefCell<Option<Coins>> = RefCell::new(None);
+	static MAX_BONDS: RefCell<u32> = RefCell::new(BondIndex::max_value() as u32);
+	static SHARE_TRANSFER_COOLDOWN: RefCell<BlockNumber> = RefCell::new(0);
+	static EXPIRED_BOND_TREASURY: RefCell<Option<AccountId>> = RefCell::new(None);
+	static MINIMUM_TRANSFER: RefCell<Coins> = RefCell::new(0);
+	static KEEP_ALIVE: RefCell<bool> = RefCell::new(true);
+}
+
+pub struct ContractionCarryover;
+
+impl Get<bool> for ContractionCarryover {
+	fn get() -> bool {
+		CONTRACTION_CARRYOVER.with(|v| *v.borrow())
+	}
+}
+
+pub struct MinSeigniorageBalance;
+
+impl Get<Coins> for MinSeigniorageBalance {
+	fn get() -> Coins {
+		MIN_SEIGNIORAGE_BALANCE.with(|v| *v.borrow())
+	}
+}
+
+pub struct SeigniorageFallback;
+
+impl Get<Option<AccountId>> for SeigniorageFallback {
+	fn get() -> Option<AccountId> {
+		SEIGNIORAGE_FALLBACK.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxBidIntakePerBlock;
+
+impl Get<Coins> for MaxBidIntakePerBlock {
+	fn get() -> Coins {
+		MAX_BID_INTAKE_PER_BLOCK.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxShareOfExpansion;
+
+impl Get<Perbill> for MaxShareOfExpansion {
+	fn get() -> Perbill {
+		MAX_SHARE_OF_EXPANSION.with(|v| *v.borrow())
+	}
+}
+
+pub struct ContractionReserve;
+
+impl Get<Option<AccountId>> for ContractionReserve {
+	fn get() -> Option<AccountId> {
+		CONTRACTION_RESERVE.with(|v| *v.borrow())
+	}
+}
+
+pub struct BidCooldown;
+
+impl Get<BlockNumber> for BidCooldown {
+	fn get() -> BlockNumber {
+		BID_COOLDOWN.with(|v| *v.borrow())
+	}
+}
+
+pub struct BondPayoutModeConfig;
+
+impl Get<BondPayoutMode> for BondPayoutModeConfig {
+	fn get() -> BondPayoutMode {
+		BOND_PAYOUT_MODE.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxCoinSupply;
+
+impl Get<Coins> for MaxCoinSupply {
+	fn get() -> Coins {
+		MAX_COIN_SUPPLY.with(|v| *v.borrow())
+	}
+}
+
+pub struct MinAcceptablePrice;
+
+impl Get<Coins> for MinAcceptablePrice {
+	fn get() -> Coins {
+		ACCEPTABLE_PRICE.with(|v| v.borrow().0)
+	}
+}
+
+pub struct MaxAcceptablePrice;
+
+impl Get<Coins> for MaxAcceptablePrice {
+	fn get() -> Coins {
+		ACCEPTABLE_PRICE.with(|v| v.borrow().1)
+	}
+}
+
+pub struct BondsExpire;
+
+impl Get<bool> for BondsExpire {
+	fn get() -> bool {
+		BONDS_EXPIRE.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaximumBondSize;
+
+impl Get<Coins> for MaximumBondSize {
+	fn get() -> Coins {
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow())
+	}
+}
+
+pub struct SeigniorageToShareholders;
+
+impl Get<bool> for SeigniorageToShareholders {
+	fn get() -> bool {
+		SEIGNIORAGE_TO_SHAREHOLDERS.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxBonds;
+
+impl Get<u32> for MaxBonds {
+	fn get() -> u32 {
+		MAX_BONDS.with(|v| *v.borrow())
+	}
+}
+
+pub struct ShareTransferCooldown;
+
+impl Get<BlockNumber> for ShareTransferCooldown {
+	fn get() -> BlockNumber {
+		SHARE_TRANSFER_COOLDOWN.with(|v| *v.borrow())
+	}
+}
+
+pub struct ExpiredBondTreasury;
+
+impl Get<Option<AccountId>> for ExpiredBondTreasury {
+	fn get() -> Option<AccountId> {
+		EXPIRED_BOND_TREASURY.with(|v| *v.borrow())
+	}
+}
+
+pub struct MinimumTransfer;
+
+impl Get<Coins> for MinimumTransfer {
+	fn get() -> Coins {
+		MINIMUM_TRANSFER.with(|v| *v.borrow())
+	}
+}
+
+pub struct KeepAlive;
+
+impl Get<bool> for KeepAlive {
+	fn get() -> bool {
+		KEEP_ALIVE.with(|v| *v.borrow())
 	}
 }
 
@@ -61,6 +329,14 @@ parameter_types! {
 	pub const InitialSupply: u64 = 100 * BaseUnit::get();
 	pub const MinimumSupply: u64 = BaseUnit::get();
 	pub const MinimumBondPrice: Perbill = Perbill::from_percent(10);
+	pub const MaxLadderSize: u32 = 5;
+	pub const MaxBatchSize: u32 = 4;
+	pub const OracleAccount: u64 = 42;
+
+	pub const EightDecimals: u8 = 8;
+	pub const EighteenDecimals: u8 = 18;
+	pub const TooManyDecimals: u8 = 40;
+	pub const PriceWindowSize: u32 = 4;
 }
 
 type AccountId = u64;
@@ -68,7 +344,7 @@ type BlockNumber = u64;
 
 impl system::Trait for Test {
 	type Origin = Origin;
-	type Call = ();
+	type Call = TestCall;
 	type Index = u64;
 	type BlockNumber = BlockNumber;
 	type Hash = H256;
@@ -76,7 +352,7 @@ impl system::Trait for Test {
 	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
@@ -89,7 +365,7 @@ impl system::Trait for Test {
 }
 
 impl Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
 	type CoinPrice = RandomPrice;
 	type ExpirationPeriod = ExpirationPeriod;
 	type MaximumBids = MaximumBids;
@@ -98,6 +374,28 @@ impl Trait for Test {
 	type InitialSupply = InitialSupply;
 	type MinimumSupply = MinimumSupply;
 	type MinimumBondPrice = MinimumBondPrice;
+	type ContractionCarryover = ContractionCarryover;
+	type MinSeigniorageBalance = MinSeigniorageBalance;
+	type MaxLadderSize = MaxLadderSize;
+	type SeigniorageFallback = SeigniorageFallback;
+	type MaxBidIntakePerBlock = MaxBidIntakePerBlock;
+	type MaxShareOfExpansion = MaxShareOfExpansion;
+	type ContractionReserve = ContractionReserve;
+	type BidCooldown = BidCooldown;
+	type BondPayoutMode = BondPayoutModeConfig;
+	type MaxCoinSupply = MaxCoinSupply;
+	type MinAcceptablePrice = MinAcceptablePrice;
+	type MaxAcceptablePrice = MaxAcceptablePrice;
+	type BondsExpire = BondsExpire;
+	type MaximumBondSize = MaximumBondSize;
+	type OracleAccount = OracleAccount;
+	type SeigniorageToShareholders = SeigniorageToShareholders;
+	type MaxBonds = MaxBonds;
+	type ShareTransferCooldown = ShareTransferCooldown;
+	type ExpiredBondTreasury = ExpiredBondTreasury;
+	type MinimumTransfer = MinimumTransfer;
+	type KeepAlive = KeepAlive;
+	type MaxBatchSize = MaxBatchSize;
 }
 
 type System = system::Module<Test>;
@@ -137,6 +435,143 @@ fn add_bond(bond: BondT) {
 	bonds.commit();
 }
 
+/// Check the invariants of the bid queue.
+///
+/// + bids are sorted from lowest to highest (the highest bid is popped first)
+/// + there are no bids without quantity
+/// + the queue holds at most `MaximumBidsLimit` bids
+/// + every bid has a valid price
+fn assert_bid_queue_valid() {
+	let bids = Stablecoin::bond_bids();
+	assert!(bids.windows(2).all(|w| w[0] <= w[1]), "bids should be sorted: {:?}", bids);
+	assert!(bids.iter().all(|b| b.quantity > 0), "bids should have a quantity: {:?}", bids);
+	assert_le!(bids.len() as u64, MaximumBidsLimit::<Test>::get());
+	for bid in bids.iter() {
+		assert_gt!(bid.price, MinimumBondPrice::get());
+		assert_le!(bid.price, Perbill::one());
+	}
+}
+
+/// Check that every Coin in the supply is accounted for, either in a balance or in the bid escrow.
+///
+/// Note: Only sums the balances of the small account ids used by the tests.
+fn assert_coins_accounted() {
+	let balances: Coins = (0..=20).map(Stablecoin::get_balance).sum();
+	assert_eq!(
+		Stablecoin::coin_supply(),
+		balances + Stablecoin::bid_escrow(),
+		"coin supply should equal the balances plus the bid escrow"
+	);
+}
+
+/// Return the events deposited by the stablecoin pallet.
+///
+/// Note: Events are only recorded for block numbers greater than zero.
+fn stablecoin_events() -> Vec<RawEvent<AccountId, BlockNumber>> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			TestEvent::stablecoin(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+// ------------------------------------------------------------
+// price
+#[test]
+fn decimal_adapter_rescales_price() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			DecimalAdapter::<Test, EightDecimalsOracle, EightDecimals>::fetch_price(),
+			1050
+		);
+		// 987.6543 rounds up
+		assert_eq!(
+			DecimalAdapter::<Test, EighteenDecimalsOracle, EighteenDecimals>::fetch_price(),
+			988
+		);
+		// prices that cannot be rescaled are reported as 0
+		assert_eq!(
+			DecimalAdapter::<Test, EightDecimalsOracle, TooManyDecimals>::fetch_price(),
+			0
+		);
+	});
+}
+
+#[test]
+fn moving_average_lags_price_spike() {
+	new_test_ext().execute_with(|| {
+		type Smoothed = MovingAverage<SpotPrice, PriceWindowSize>;
+		let set_spot = |price: Coins| SPOT_PRICE.with(|v| *v.borrow_mut() = price);
+		let record = || {
+			Smoothed::record_price();
+			Smoothed::fetch_price()
+		};
+		// nothing recorded yet
+		assert_eq!(Smoothed::fetch_price(), 0);
+		for _ in 0..PriceWindowSize::get() {
+			assert_eq!(record(), TEST_BASE_UNIT);
+		}
+
+		set_spot(3 * TEST_BASE_UNIT);
+		// fetching does not record the new price
+		assert_eq!(Smoothed::fetch_price(), TEST_BASE_UNIT);
+		// (3 * 1000 + 3000) / 4
+		assert_eq!(record(), 1500);
+		assert_eq!(record(), 2000);
+		assert_eq!(Smoothed::fetch_price(), 2000);
+		assert_eq!(record(), 2500);
+		// the window only holds the spike now
+		assert_eq!(record(), 3 * TEST_BASE_UNIT);
+		assert_eq!(Stablecoin::price_window().len(), PriceWindowSize::get() as usize);
+
+		// invalid readings are ignored
+		set_spot(0);
+		assert_eq!(record(), 3 * TEST_BASE_UNIT);
+		set_spot(TEST_BASE_UNIT);
+	});
+}
+
+#[test]
+fn oracle_submits_price() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		// no price submitted yet
+		assert_eq!(StoredPrice::<Test>::fetch_price(), 0);
+
+		assert_ok!(Stablecoin::submit_price(Origin::signed(OracleAccount::get()), 1100));
+		assert_eq!(StoredPrice::<Test>::fetch_price(), 1100);
+		// later submissions overwrite earlier ones
+		assert_ok!(Stablecoin::submit_price(Origin::signed(OracleAccount::get()), 900));
+		assert_eq!(StoredPrice::<Test>::fetch_price(), 900);
+		assert!(stablecoin_events().contains(&RawEvent::PriceSubmitted(900)));
+	});
+}
+
+#[test]
+fn only_oracle_can_submit_price() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Stablecoin::submit_price(Origin::signed(1), 1100), Error::<Test>::NotOracle);
+		assert_eq!(Stablecoin::current_price(), 0);
+	});
+}
+
+#[test]
+fn stored_price_drives_rebase() {
+	new_test_ext().execute_with(|| {
+		let supply = Stablecoin::coin_supply();
+		// price too low --> expand
+		assert_ok!(Stablecoin::submit_price(Origin::signed(OracleAccount::get()), TEST_BASE_UNIT / 2));
+		assert_ok!(Stablecoin::on_block_with_price(2, StoredPrice::<Test>::fetch_price()));
+		assert_gt!(Stablecoin::coin_supply(), supply);
+		assert_eq!(
+			Stablecoin::last_rebase(),
+			Some((2, SupplyAction::Expand(Stablecoin::coin_supply() - supply)))
+		);
+	});
+}
+
 // ------------------------------------------------------------
 // init tests
 #[test]
@@ -160,6 +595,138 @@ fn init_test() {
 		);
 		let share_supply: u64 = shares.iter().map(|(_a, s)| s).sum();
 		assert_eq!(share_supply, 10);
+		assert_coins_accounted();
+	});
+}
+
+#[test]
+fn init_with_custom_share_counts() {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let shareholders = vec![(1, 7), (2, 3)];
+	let _ = GenesisConfig::<Test> {
+		shareholders: shareholders.clone(),
+	}
+	.assimilate_storage(&mut storage);
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		assert_eq!(Stablecoin::shares(), shareholders);
+		assert_eq!(Stablecoin::checked_share_supply(&Stablecoin::shares()), Ok(10));
+		assert_eq!(Stablecoin::get_balance(1), InitialSupply::get() * 7 / 10);
+		assert_eq!(Stablecoin::get_balance(2), InitialSupply::get() * 3 / 10);
+	});
+}
+
+#[test]
+fn init_distributes_supply_proportionally() {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	// the share supply does not divide the initial supply evenly
+	let shareholders = vec![(1, 1), (2, 2), (3, 4)];
+	let _ = GenesisConfig::<Test> {
+		shareholders: shareholders.clone(),
+	}
+	.assimilate_storage(&mut storage);
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		assert_eq!(Stablecoin::coin_supply(), InitialSupply::get());
+		let total_balance: Coins = shareholders.iter().map(|(acc, _s)| Stablecoin::get_balance(acc)).sum();
+		assert_eq!(total_balance, InitialSupply::get());
+		for (acc, shares) in shareholders {
+			let proportional = InitialSupply::get() * shares / 7;
+			assert_ge!(Stablecoin::get_balance(acc), proportional);
+			assert_le!(Stablecoin::get_balance(acc), proportional + 1);
+		}
+		assert_coins_accounted();
+	});
+}
+
+#[test]
+#[should_panic(expected = "need at least one shareholder")]
+fn init_without_shareholders_fails() {
+	new_test_ext_with(vec![]);
+}
+
+#[test]
+#[should_panic(expected = "need at least one shareholder with a share")]
+fn init_with_zero_shares_fails() {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let shareholders = vec![(1, 0), (2, 0)];
+	let _ = GenesisConfig::<Test> { shareholders }.assimilate_storage(&mut storage);
+}
+
+#[test]
+#[should_panic(expected = "initialization handout should not fail")]
+fn init_with_overflowing_shares_fails() {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let shareholders = vec![(1, u64::max_value()), (2, 1)];
+	let _ = GenesisConfig::<Test> { shareholders }.assimilate_storage(&mut storage);
+}
+
+#[test]
+#[should_panic(expected = "max bonds need to fit into the bond queue")]
+fn init_with_too_many_max_bonds_fails() {
+	MAX_BONDS.with(|v| *v.borrow_mut() = BondIndex::max_value() as u32 + 1);
+	new_test_ext();
+}
+
+#[test]
+fn init_only_once() {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let _ = GenesisConfig::<Test> { shareholders: vec![(1, 1)] }.assimilate_storage(&mut storage);
+	let mut second = storage.clone();
+	let result = std::panic::catch_unwind(move || {
+		let _ = GenesisConfig::<Test> { shareholders: vec![(2, 1)] }.assimilate_storage(&mut second);
+	});
+	assert!(result.is_err(), "second initialization should be rejected");
+
+	let mut ext = sp_io::TestExternalities::from(storage);
+	ext.execute_with(|| {
+		assert_eq!(Stablecoin::get_balance(1), InitialSupply::get());
+		assert_eq!(Stablecoin::get_balance(2), 0);
+		assert_eq!(Stablecoin::shares(), vec![(1, 1)]);
+	});
+}
+
+#[test]
+fn init_sets_exactly_the_initial_supply() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::coin_supply(), InitialSupply::get());
+		let total_balance: Coins = (1..=10).map(Stablecoin::get_balance).sum();
+		assert_eq!(total_balance, InitialSupply::get());
+	});
+	new_test_ext_with(vec![7]).execute_with(|| {
+		assert_eq!(Stablecoin::coin_supply(), InitialSupply::get());
+		assert_eq!(Stablecoin::get_balance(7), InitialSupply::get());
+		// there is no founder receiving Coins besides the shareholders
+		for acc in (1..=6).chain(8..=10) {
+			assert_eq!(Stablecoin::get_balance(acc), 0);
+		}
+	});
+}
+
+#[test]
+fn init_allocation_is_reported_by_overview() {
+	new_test_ext().execute_with(|| {
+		let overview = Stablecoin::overview();
+		assert_eq!(overview.coin_supply, InitialSupply::get());
+		assert_eq!(overview.share_supply, 10);
+		assert_eq!(overview.shareholder_count, 10);
+	});
+	new_test_ext_with(vec![7]).execute_with(|| {
+		let overview = Stablecoin::overview();
+		assert_eq!(overview.coin_supply, InitialSupply::get());
+		assert_eq!(overview.share_supply, 1);
+		assert_eq!(overview.shareholder_count, 1);
+	});
+}
+
+#[test]
+fn init_sets_the_storage_version() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::storage_version(), PALLET_VERSION);
+
+		// chains initialized before the version was introduced are tagged on upgrade
+		<StorageVersion>::kill();
+		assert_eq!(Stablecoin::storage_version(), 0);
+		<Stablecoin as OnRuntimeUpgrade>::on_runtime_upgrade();
+		assert_eq!(Stablecoin::storage_version(), PALLET_VERSION);
 	});
 }
 
@@ -173,87 +740,687 @@ fn transfer_test() {
 		let amount = TEST_BASE_UNIT;
 		let from_balance_before = Stablecoin::get_balance(first_acc);
 		let to_balance_before = Stablecoin::get_balance(second_acc);
+		assert_coins_accounted();
 		assert_ok!(Stablecoin::transfer_from_to(&first_acc, &second_acc, amount));
+		assert_coins_accounted();
 		assert_eq!(Stablecoin::get_balance(first_acc), from_balance_before - amount);
 		assert_eq!(Stablecoin::get_balance(second_acc), to_balance_before + amount);
 	});
 }
 
-// ------------------------------------------------------------
-// currency trait
 #[test]
-fn slash_test() {
+fn force_transfer_test() {
 	new_test_ext().execute_with(|| {
-		let acc = 1;
 		let amount = TEST_BASE_UNIT;
-		let balance_before = Stablecoin::get_balance(acc);
-		assert_eq!(Stablecoin::slash(&acc, amount), 0);
-		assert_eq!(Stablecoin::get_balance(acc), balance_before - amount);
+		let from_balance_before = Stablecoin::get_balance(1);
+		let to_balance_before = Stablecoin::get_balance(2);
+		assert_noop!(
+			Stablecoin::force_transfer(Origin::signed(1), 1, 2, amount),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Stablecoin::force_transfer(Origin::signed(2), 1, 2, amount),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Stablecoin::force_transfer(Origin::ROOT, 1, 2, from_balance_before + 1),
+			Error::<Test>::InsufficientBalance
+		);
+
+		assert_ok!(Stablecoin::force_transfer(Origin::ROOT, 1, 2, amount));
+		assert_eq!(Stablecoin::get_balance(1), from_balance_before - amount);
+		assert_eq!(Stablecoin::get_balance(2), to_balance_before + amount);
 	});
 }
 
-// ------------------------------------------------------------
-// bids
 #[test]
-fn bids_are_sorted_highest_to_lowest() {
+fn transfer_to_self_is_noop() {
 	new_test_ext().execute_with(|| {
-		let bid_amount = 5 * BaseUnit::get();
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(25), bid_amount));
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(33), bid_amount));
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(50), bid_amount));
-
-		let bids = Stablecoin::bond_bids();
-		let prices: Vec<_> = bids.into_iter().map(|Bid { price, .. }| price).collect();
-		// largest bid is stored last so we can pop
-		assert_eq!(
-			prices,
-			vec![
-				Perbill::from_percent(25),
-				Perbill::from_percent(33),
-				Perbill::from_percent(50),
-			]
+		System::set_block_number(1);
+		let balance_before = Stablecoin::get_balance(1);
+		assert_noop!(
+			Stablecoin::send_coins(Origin::signed(1), 1, balance_before + 1),
+			Error::<Test>::InsufficientBalance
 		);
+
+		let root_before = sp_io::storage::root();
+		assert_ok!(Stablecoin::send_coins(Origin::signed(1), 1, TEST_BASE_UNIT));
+		assert_ok!(Stablecoin::force_transfer(Origin::ROOT, 1, 1, TEST_BASE_UNIT));
+		assert_eq!(Stablecoin::get_balance(1), balance_before);
+		assert!(stablecoin_events().is_empty());
+		assert_eq!(sp_io::storage::root(), root_before);
 	});
 }
 
 #[test]
-fn amount_of_bids_is_limited() {
+fn transfer_all_test() {
 	new_test_ext().execute_with(|| {
-		let bid_amount = 5 * BaseUnit::get();
-		for _i in 0..(2 * MaximumBids::get()) {
-			Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(25), bid_amount));
-		}
+		System::set_block_number(1);
+		let (from, to) = (42, 2);
+		assert_noop!(
+			Stablecoin::transfer_all(Origin::signed(from), to),
+			Error::<Test>::InsufficientBalance
+		);
 
-		assert_eq!(Stablecoin::bond_bids().len() as u64, MaximumBids::get());
+		assert_ok!(Stablecoin::send_coins(Origin::signed(1), from, 3 * TEST_BASE_UNIT));
+		let to_before = Stablecoin::get_balance(to);
+		assert_ok!(Stablecoin::transfer_all(Origin::signed(from), to));
+		assert_eq!(Stablecoin::get_balance(from), 0);
+		assert_eq!(Stablecoin::get_balance(to), to_before + 3 * TEST_BASE_UNIT);
+		assert_coins_accounted();
+		assert!(stablecoin_events().contains(&RawEvent::Transfer(from, to, 3 * TEST_BASE_UNIT)));
 	});
 }
 
 #[test]
-fn truncated_bids_are_refunded() {
-	new_test_ext_with(vec![1]).execute_with(|| {
-		let price = Perbill::from_percent(25);
-		let quantity = BaseUnit::get();
-		for _i in 0..(MaximumBids::get() + 1) {
-			assert_ok!(Stablecoin::bid_for_bond(Origin::signed(1), price, quantity));
-		}
-
-		assert_eq!(Stablecoin::bond_bids().len() as u64, MaximumBids::get());
-		let expected = InitialSupply::get() - price * quantity * (MaximumBids::get() as u64);
+fn send_coins_below_minimum_fails() {
+	new_test_ext().execute_with(|| {
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = TEST_BASE_UNIT);
+		assert_noop!(
+			Stablecoin::send_coins(Origin::signed(1), 2, TEST_BASE_UNIT - 1),
+			Error::<Test>::BelowMinimumTransfer
+		);
+		assert_ok!(Stablecoin::send_coins(Origin::signed(1), 2, TEST_BASE_UNIT));
+		// root transfers are not restricted
+		assert_ok!(Stablecoin::force_transfer(Origin::ROOT, 1, 2, 1));
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = 0);
+	});
+}
+
+#[test]
+fn send_coins_dust_handling() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = TEST_BASE_UNIT);
+		let balance = Stablecoin::get_balance(1);
+		let to_before = Stablecoin::get_balance(2);
+		let amount = balance - TEST_BASE_UNIT / 2;
+		assert_noop!(
+			Stablecoin::send_coins(Origin::signed(1), 2, amount),
+			Error::<Test>::WouldLeaveDust
+		);
+
+		KEEP_ALIVE.with(|v| *v.borrow_mut() = false);
+		assert_ok!(Stablecoin::send_coins(Origin::signed(1), 2, amount));
+		assert_eq!(Stablecoin::get_balance(1), 0);
+		assert_eq!(Stablecoin::get_balance(2), to_before + balance);
+		assert_coins_accounted();
+		assert_eq!(stablecoin_events(), vec![RawEvent::Transfer(1, 2, balance)]);
+		KEEP_ALIVE.with(|v| *v.borrow_mut() = true);
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = 0);
+	});
+}
+
+#[test]
+fn batch_transfer_test() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let balance = Stablecoin::get_balance(1);
+		let before_2 = Stablecoin::get_balance(2);
+		let before_3 = Stablecoin::get_balance(3);
+		let amount = BaseUnit::get();
+		assert_ok!(Stablecoin::batch_transfer(
+			Origin::signed(1),
+			vec![(2, amount), (3, 2 * amount), (1, amount)]
+		));
+		assert_eq!(Stablecoin::get_balance(1), balance - 3 * amount);
+		assert_eq!(Stablecoin::get_balance(2), before_2 + amount);
+		assert_eq!(Stablecoin::get_balance(3), before_3 + 2 * amount);
+		assert_coins_accounted();
+		assert_eq!(
+			stablecoin_events(),
+			vec![RawEvent::Transfer(1, 2, amount), RawEvent::Transfer(1, 3, 2 * amount)]
+		);
+	});
+}
+
+#[test]
+fn batch_transfer_is_atomic() {
+	new_test_ext().execute_with(|| {
+		let balance = Stablecoin::get_balance(1);
+		// the second transfer exceeds the balance in total
+		assert_noop!(
+			Stablecoin::batch_transfer(Origin::signed(1), vec![(2, balance / 2), (3, balance)]),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_noop!(
+			Stablecoin::batch_transfer(Origin::signed(1), vec![(2, balance), (3, Coins::max_value())]),
+			Error::<Test>::InsufficientBalance
+		);
+
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = TEST_BASE_UNIT);
+		assert_noop!(
+			Stablecoin::batch_transfer(
+				Origin::signed(1),
+				vec![(2, TEST_BASE_UNIT), (3, TEST_BASE_UNIT / 2)]
+			),
+			Error::<Test>::BelowMinimumTransfer
+		);
+		// leaves half the minimum
+		let rest = balance - 3 * TEST_BASE_UNIT / 2;
+		assert_noop!(
+			Stablecoin::batch_transfer(Origin::signed(1), vec![(2, TEST_BASE_UNIT), (3, rest)]),
+			Error::<Test>::WouldLeaveDust
+		);
+		MINIMUM_TRANSFER.with(|v| *v.borrow_mut() = 0);
+
+		let batch = vec![(2, 1); MaxBatchSize::get() as usize + 1];
+		assert_noop!(
+			Stablecoin::batch_transfer(Origin::signed(1), batch),
+			Error::<Test>::BatchTooLarge
+		);
+	});
+}
+
+#[test]
+fn batch_transfer_weight_scales_with_size() {
+	let batch = |len: usize| vec![(2, BaseUnit::get()); len];
+	let weight = |len| {
+		Call::<Test>::batch_transfer(batch(len))
+			.get_dispatch_info()
+			.weight
+	};
+	assert_eq!(weight(0), TRANSFER_WEIGHT);
+	assert_eq!(weight(1), 2 * TRANSFER_WEIGHT);
+	assert_eq!(weight(4), 5 * TRANSFER_WEIGHT);
+	assert_eq!(weight(10) - weight(4), 6 * TRANSFER_WEIGHT);
+}
+
+// ------------------------------------------------------------
+// currency trait
+#[test]
+fn currency_balances_test() {
+	new_test_ext().execute_with(|| {
+		let acc = 1;
+		assert_eq!(
+			<Stablecoin as BasicCurrency<AccountId>>::total_issuance(),
+			Stablecoin::coin_supply()
+		);
+		assert_eq!(Stablecoin::total_balance(&acc), Stablecoin::get_balance(acc));
+		assert_eq!(Stablecoin::free_balance(&acc), Stablecoin::get_balance(acc));
+		assert!(Stablecoin::can_slash(&acc, Stablecoin::get_balance(acc)));
+		assert!(!Stablecoin::can_slash(&acc, Stablecoin::get_balance(acc) + 1));
+		assert!(Stablecoin::can_slash(&42, 0));
+	});
+}
+
+#[test]
+fn currency_transfer_test() {
+	new_test_ext().execute_with(|| {
+		let (from, to) = (1, 42);
+		let amount = TEST_BASE_UNIT;
+		let from_before = Stablecoin::get_balance(from);
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(<Stablecoin as BasicCurrency<AccountId>>::transfer(&from, &to, amount));
+		assert_eq!(Stablecoin::get_balance(from), from_before - amount);
+		assert_eq!(Stablecoin::get_balance(to), amount);
+		assert_eq!(Stablecoin::coin_supply(), supply);
+
+		assert_noop!(
+			<Stablecoin as BasicCurrency<AccountId>>::transfer(&to, &from, amount + 1),
+			Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn currency_cannot_change_issuance() {
+	new_test_ext().execute_with(|| {
+		let acc = 1;
+		let balance = Stablecoin::get_balance(acc);
+		let supply = Stablecoin::coin_supply();
+		assert!(Stablecoin::deposit(&acc, TEST_BASE_UNIT).is_err());
+		assert!(Stablecoin::withdraw(&acc, TEST_BASE_UNIT).is_err());
+		assert_ok!(Stablecoin::ensure_can_withdraw(&acc, 0));
+		assert!(Stablecoin::ensure_can_withdraw(&acc, TEST_BASE_UNIT).is_err());
+		assert_eq!(Stablecoin::get_balance(acc), balance);
+		assert_eq!(Stablecoin::coin_supply(), supply);
+	});
+}
+
+#[test]
+fn slash_test() {
+	new_test_ext().execute_with(|| {
+		let acc = 1;
+		let amount = TEST_BASE_UNIT;
+		let balance_before = Stablecoin::get_balance(acc);
+		assert_eq!(Stablecoin::slash(&acc, amount), 0);
+		assert_eq!(Stablecoin::get_balance(acc), balance_before - amount);
+	});
+}
+
+// ------------------------------------------------------------
+// bids
+#[test]
+fn bid_for_bond_limits_cost() {
+	new_test_ext().execute_with(|| {
+		// 33% of 5001 is 1650.33 --> payment of 1650
+		let price = Perbill::from_percent(33);
+		let quantity = 5 * BaseUnit::get() + 1;
+		let balance = Stablecoin::get_balance(1);
+		assert_noop!(
+			Stablecoin::bid_for_bond(Origin::signed(1), price, quantity, 1649),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_ok!(Stablecoin::bid_for_bond(Origin::signed(1), price, quantity, 1650));
+		assert_eq!(Stablecoin::get_balance(1), balance - 1650);
+		assert_eq!(Stablecoin::bid_count(), 1);
+		assert_bid_queue_valid();
+	});
+}
+
+#[test]
+fn bids_are_sorted_highest_to_lowest() {
+	new_test_ext().execute_with(|| {
+		let bid_amount = 5 * BaseUnit::get();
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(25), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(33), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), bid_amount));
+		assert_bid_queue_valid();
+
+		let bids = Stablecoin::bond_bids();
+		let prices: Vec<_> = bids.into_iter().map(|Bid { price, .. }| price).collect();
+		// largest bid is stored last so we can pop
+		assert_eq!(
+			prices,
+			vec![
+				Perbill::from_percent(25),
+				Perbill::from_percent(33),
+				Perbill::from_percent(50),
+			]
+		);
+	});
+}
+
+#[test]
+fn amount_of_bids_is_limited() {
+	new_test_ext().execute_with(|| {
+		let bid_amount = 5 * BaseUnit::get();
+		for _i in 0..(2 * MaximumBids::get()) {
+			Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(25), bid_amount));
+			assert_bid_queue_valid();
+		}
+
+		assert_eq!(Stablecoin::bond_bids().len() as u64, MaximumBids::get());
+	});
+}
+
+#[test]
+fn truncated_bids_are_refunded() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		let price = Perbill::from_percent(25);
+		let quantity = BaseUnit::get();
+		for _i in 0..(MaximumBids::get() + 1) {
+			assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+			assert_bid_queue_valid();
+		}
+
+		assert_eq!(Stablecoin::bond_bids().len() as u64, MaximumBids::get());
+		let expected = InitialSupply::get() - price * quantity * (MaximumBids::get() as u64);
 		assert_eq!(Stablecoin::get_balance(1), expected);
 	});
 }
 
+#[test]
+fn zero_maximum_bids_disables_bidding() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		assert_ok!(Stablecoin::set_maximum_bids(Origin::ROOT, Some(0)));
+		assert_bid_queue_valid();
+		let price = Perbill::from_percent(80);
+		let quantity = BaseUnit::get();
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity),
+			Error::<Test>::BiddingDisabled
+		);
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), vec![(price, quantity)]),
+			Error::<Test>::BiddingDisabled
+		);
+		assert_eq!(Stablecoin::get_balance(1), InitialSupply::get());
+		assert_eq!(Stablecoin::bid_count(), 0);
+
+		assert_ok!(Stablecoin::set_maximum_bids(Origin::ROOT, None));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_bid_queue_valid();
+	});
+}
+
+#[test]
+fn lowering_maximum_bids_truncates_and_refunds() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let quantity = BaseUnit::get();
+		for (acc, percent) in vec![(1, 25), (2, 30), (1, 50), (2, 60), (1, 75)] {
+			assert_ok!(Stablecoin::bid_for_bond_simple(
+				Origin::signed(acc),
+				Perbill::from_percent(percent),
+				quantity
+			));
+			assert_bid_queue_valid();
+		}
+		let balance_1 = Stablecoin::get_balance(1);
+		let balance_2 = Stablecoin::get_balance(2);
+
+		assert_noop!(
+			Stablecoin::set_maximum_bids(Origin::signed(1), Some(3)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Stablecoin::set_maximum_bids(Origin::ROOT, Some(3)));
+		assert_bid_queue_valid();
+
+		let prices: Vec<_> = Stablecoin::bond_bids()
+			.into_iter()
+			.map(|Bid { price, .. }| price)
+			.collect();
+		assert_eq!(
+			prices,
+			vec![
+				Perbill::from_percent(50),
+				Perbill::from_percent(60),
+				Perbill::from_percent(75),
+			]
+		);
+		// the two lowest bids were refunded
+		assert_eq!(
+			Stablecoin::get_balance(1),
+			balance_1 + Perbill::from_percent(25) * quantity
+		);
+		assert_eq!(
+			Stablecoin::get_balance(2),
+			balance_2 + Perbill::from_percent(30) * quantity
+		);
+
+		// new bids respect the override
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_percent(80),
+			quantity
+		));
+		assert_bid_queue_valid();
+		assert_eq!(Stablecoin::bond_bids().len(), 3);
+
+		// resetting the override falls back to `MaximumBids`
+		assert_ok!(Stablecoin::set_maximum_bids(Origin::ROOT, None));
+		assert_bid_queue_valid();
+		assert_eq!(MaximumBidsLimit::<Test>::get(), MaximumBids::get());
+	});
+}
+
+#[test]
+fn remove_coins_rounds_down_by_less_than_one() {
+	new_test_ext().execute_with(|| {
+		let prices = [
+			Perbill::from_parts(MinimumBondPrice::get().deconstruct() + 1),
+			Perbill::from_percent(25),
+			Perbill::from_parts(333_333_333),
+			Perbill::from_percent(50),
+			Perbill::from_parts(666_666_667),
+			Perbill::from_percent(80),
+			Perbill::from_parts(Perbill::ACCURACY - 1),
+			Perbill::one(),
+		];
+		// `coins * ACCURACY` needs to fit into `u64` for prices that cannot be reduced
+		let coins = [1, 3, 7, 999, TEST_BASE_UNIT, 12_345, 1_000_000_007];
+		let quantity = 1_000_000_000_000_000;
+		for price in prices.iter() {
+			for coins in coins.iter() {
+				let mut bid = Stablecoin::new_bid(1, *price, quantity);
+				let removed = bid.remove_coins(*coins).expect("should not overflow or underflow");
+				assert_eq!(bid.quantity, quantity - removed);
+				// `removed` is the exact quotient `coins / price` rounded down, so the truncation
+				// error is less than one unit of quantity.
+				let exact_numerator = *coins as u128 * Perbill::ACCURACY as u128;
+				let denominator = price.deconstruct() as u128;
+				assert_le!(removed as u128 * denominator, exact_numerator);
+				assert_gt!((removed as u128 + 1) * denominator, exact_numerator);
+			}
+		}
+	});
+}
+
+#[test]
+fn bid_price_range() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		let quantity = BaseUnit::get();
+		// `MinimumBondPrice` itself is not a valid price
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), MinimumBondPrice::get(), quantity),
+			Error::<Test>::BondPriceTooLow
+		);
+		// constructors clamp to 100% ...
+		assert_eq!(Perbill::from_percent(150), Perbill::one());
+		// ... but decoding does not
+		let over_100 = Perbill::decode(&mut &(Perbill::ACCURACY + 1).encode()[..]).unwrap();
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), over_100, quantity),
+			Error::<Test>::BondPriceOver100Percent
+		);
+
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), Perbill::one(), quantity));
+		assert_bid_queue_valid();
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_parts(MinimumBondPrice::get().deconstruct() + 1),
+			quantity
+		));
+		assert_bid_queue_valid();
+	});
+}
+
+#[test]
+fn bid_ladder_test() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let quantity = 2 * BaseUnit::get();
+		let ladder = vec![
+			(Perbill::from_percent(90), quantity),
+			(Perbill::from_percent(80), quantity),
+			(Perbill::from_percent(70), quantity),
+		];
+		let balance_before = Stablecoin::get_balance(1);
+		assert_ok!(Stablecoin::bid_ladder(Origin::signed(1), ladder));
+		assert_bid_queue_valid();
+
+		let bids: Vec<(AccountId, Perbill)> = Stablecoin::bond_bids()
+			.into_iter()
+			.map(|Bid { account, price, .. }| (account, price))
+			.collect();
+		assert_eq!(
+			bids,
+			vec![
+				(1, Perbill::from_percent(70)),
+				(1, Perbill::from_percent(80)),
+				(1, Perbill::from_percent(90)),
+			]
+		);
+		assert_eq!(
+			Stablecoin::get_balance(1),
+			balance_before - Perbill::from_percent(240) * quantity
+		);
+	});
+}
+
+#[test]
+fn bid_ladder_is_atomic() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let balance = Stablecoin::get_balance(1);
+		// the second bid exceeds the balance in total
+		let ladder = vec![
+			(Perbill::from_percent(90), balance / 2),
+			(Perbill::from_percent(80), balance),
+		];
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), ladder),
+			Error::<Test>::InsufficientBalance
+		);
+
+		let ladder = vec![
+			(Perbill::from_percent(90), BaseUnit::get()),
+			(Perbill::from_percent(5), BaseUnit::get()),
+		];
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), ladder),
+			Error::<Test>::BondPriceTooLow
+		);
+
+		let ladder = vec![(Perbill::from_percent(90), BaseUnit::get()); MaxLadderSize::get() as usize + 1];
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), ladder),
+			Error::<Test>::LadderTooLarge
+		);
+
+		let ladder = vec![(Perbill::from_percent(90), BaseUnit::get()); MaxBatchSize::get() as usize + 1];
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), ladder),
+			Error::<Test>::BatchTooLarge
+		);
+	});
+}
+
+#[test]
+fn empty_bid_ladder_is_rejected() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		// neither starts the bid cooldown nor emits events
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), vec![]),
+			Error::<Test>::EmptyLadder
+		);
+		assert!(stablecoin_events().is_empty());
+	});
+}
+
+#[test]
+fn bid_ladder_weight_scales_with_size() {
+	let ladder = |len: usize| vec![(Perbill::from_percent(90), BaseUnit::get()); len];
+	let weight = |len| Call::<Test>::bid_ladder(ladder(len)).get_dispatch_info().weight;
+	assert_eq!(weight(0), BID_WEIGHT);
+	assert_eq!(weight(1), 2 * BID_WEIGHT);
+	assert_eq!(weight(5), 6 * BID_WEIGHT);
+	assert_eq!(weight(10) - weight(5), 5 * BID_WEIGHT);
+}
+
+#[test]
+fn bid_cooldown_test() {
+	new_test_ext().execute_with(|| {
+		BID_COOLDOWN.with(|v| *v.borrow_mut() = 3);
+		let price = Perbill::from_percent(80);
+		let quantity = BaseUnit::get();
+		System::set_block_number(1);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_bid_queue_valid();
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity),
+			Error::<Test>::BidOnCooldown
+		);
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), vec![(price, quantity)]),
+			Error::<Test>::BidOnCooldown
+		);
+		// other accounts are not affected
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), price, quantity));
+		assert_bid_queue_valid();
+
+		System::set_block_number(3);
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity),
+			Error::<Test>::BidOnCooldown
+		);
+		System::set_block_number(4);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_bid_queue_valid();
+		assert_eq!(Stablecoin::bids_of(&1).len(), 2);
+		BID_COOLDOWN.with(|v| *v.borrow_mut() = 0);
+	});
+}
+
+#[test]
+fn bid_intake_is_limited_per_block() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let bid_price = Perbill::from_percent(50);
+		let quantity = 2 * BaseUnit::get();
+		MAX_BID_INTAKE_PER_BLOCK.with(|v| *v.borrow_mut() = 3 * BaseUnit::get());
+
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), bid_price, quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), bid_price, quantity));
+		assert_bid_queue_valid();
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), bid_price, quantity),
+			Error::<Test>::BidIntakeExceeded
+		);
+
+		// the intake is reset in the next block
+		System::set_block_number(System::block_number() + 1);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), bid_price, quantity));
+		assert_bid_queue_valid();
+		MAX_BID_INTAKE_PER_BLOCK.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn bid_intake_overflow_exceeds_the_limit() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		// an intake that would wrap around to a small value on overflow
+		<BidIntake<Test>>::put((System::block_number(), Coins::max_value() - 1));
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(
+				Origin::signed(1),
+				Perbill::from_percent(50),
+				2 * BaseUnit::get()
+			),
+			Error::<Test>::BidIntakeExceeded
+		);
+		assert_noop!(
+			Stablecoin::bid_ladder(Origin::signed(1), vec![(Perbill::from_percent(50), 2 * BaseUnit::get())]),
+			Error::<Test>::BidIntakeExceeded
+		);
+	});
+}
+
+#[test]
+fn bids_record_placement_block() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let quantity = BaseUnit::get();
+		System::set_block_number(3);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), Perbill::from_percent(80), quantity));
+		assert_bid_queue_valid();
+		System::set_block_number(5);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), Perbill::from_percent(70), quantity));
+		assert_bid_queue_valid();
+		System::set_block_number(8);
+		assert_ok!(Stablecoin::bid_ladder(
+			Origin::signed(1),
+			vec![(Perbill::from_percent(60), quantity)]
+		));
+		assert_bid_queue_valid();
+
+		let placed: Vec<(Perbill, BlockNumber)> = Stablecoin::bids_of(&1)
+			.into_iter()
+			.map(|Bid { price, placed_at, .. }| (price, placed_at))
+			.collect();
+		assert_eq!(
+			placed,
+			vec![(Perbill::from_percent(60), 8), (Perbill::from_percent(80), 3)]
+		);
+		let placed: Vec<BlockNumber> = Stablecoin::bids_of(&2)
+			.into_iter()
+			.map(|Bid { placed_at, .. }| placed_at)
+			.collect();
+		assert_eq!(placed, vec![5]);
+	});
+}
+
 #[test]
 fn cancel_all_bids_test() {
 	new_test_ext().execute_with(|| {
 		let bid_amount = 5 * BaseUnit::get();
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(25), bid_amount));
-		Stablecoin::add_bid(Bid::new(2, Perbill::from_percent(33), bid_amount));
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(50), bid_amount));
-		Stablecoin::add_bid(Bid::new(3, Perbill::from_percent(50), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(25), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(33), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(50), bid_amount));
+		assert_bid_queue_valid();
 		assert_eq!(Stablecoin::bond_bids().len(), 4);
 
 		assert_ok!(Stablecoin::cancel_all_bids(Origin::signed(1)));
+		assert_bid_queue_valid();
 
 		let bids = Stablecoin::bond_bids();
 		assert_eq!(bids.len(), 2);
@@ -263,21 +1430,88 @@ fn cancel_all_bids_test() {
 	});
 }
 
+#[test]
+fn cancel_bid_test() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let price = Perbill::from_percent(80);
+		let quantity = 5 * BaseUnit::get();
+		let balance_before = Stablecoin::get_balance(1);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), Perbill::from_percent(70), quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), price, quantity));
+		assert_noop!(
+			Stablecoin::cancel_bid(Origin::signed(1), Perbill::from_percent(50)),
+			Error::<Test>::NoMatchingBid
+		);
+
+		// partially fill the first bid
+		let filled = BaseUnit::get();
+		assert_ok!(Stablecoin::contract_supply(Stablecoin::coin_supply(), filled));
+
+		assert_ok!(Stablecoin::cancel_bid(Origin::signed(1), price));
+		assert_bid_queue_valid();
+		let refunded = 2 * (price * quantity) - filled;
+		assert!(stablecoin_events().contains(&RawEvent::BidCancelled(1, refunded)));
+		let paid = 2 * (price * quantity) + Perbill::from_percent(70) * quantity;
+		assert_eq!(Stablecoin::get_balance(1), balance_before - paid + refunded);
+		// the other bids are untouched
+		let bids: Vec<(AccountId, Perbill)> = Stablecoin::bond_bids()
+			.into_iter()
+			.map(|Bid { account, price, .. }| (account, price))
+			.collect();
+		assert_eq!(bids, vec![(1, Perbill::from_percent(70)), (2, price)]);
+		assert_noop!(
+			Stablecoin::cancel_bid(Origin::signed(1), price),
+			Error::<Test>::NoMatchingBid
+		);
+	});
+}
+
+#[test]
+fn cancel_bids_refunds_at_most_the_escrow() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let price = Perbill::from_percent(80);
+		let quantity = 5 * BaseUnit::get();
+		let payment = price * quantity;
+		let balance_before = Stablecoin::get_balance(1);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity));
+		assert_bid_queue_valid();
+		assert_eq!(Stablecoin::bid_escrow(), payment);
+
+		// simulate a shortfall of the escrow
+		let shortfall = BaseUnit::get();
+		<BidEscrow>::put(payment - shortfall);
+		assert_ok!(Stablecoin::cancel_all_bids(Origin::signed(1)));
+		assert_bid_queue_valid();
+
+		assert_eq!(Stablecoin::get_balance(1), balance_before - shortfall);
+		assert_eq!(Stablecoin::bid_escrow(), 0);
+		let events = stablecoin_events();
+		assert!(events.contains(&RawEvent::PartialRefund(1, payment, payment - shortfall)));
+		assert!(events.contains(&RawEvent::RefundedBid(1, payment - shortfall)));
+	});
+}
+
 #[test]
 fn cancel_selected_bids_test() {
 	new_test_ext().execute_with(|| {
 		let bid_amount = 5 * BaseUnit::get();
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(25), bid_amount));
-		Stablecoin::add_bid(Bid::new(2, Perbill::from_percent(33), bid_amount));
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(45), bid_amount));
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(50), bid_amount));
-		Stablecoin::add_bid(Bid::new(3, Perbill::from_percent(55), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(25), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(33), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(45), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), bid_amount));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(55), bid_amount));
+		assert_bid_queue_valid();
 		assert_eq!(Stablecoin::bond_bids().len(), 5);
 
 		assert_ok!(Stablecoin::cancel_bids_at_or_below(
 			Origin::signed(1),
 			Perbill::from_percent(45)
 		));
+		assert_bid_queue_valid();
 
 		let bids = Stablecoin::bond_bids();
 		assert_eq!(bids.len(), 3);
@@ -297,6 +1531,48 @@ fn cancel_selected_bids_test() {
 	});
 }
 
+#[test]
+fn unaffordable_bid_is_rejected_on_validation() {
+	new_test_ext().execute_with(|| {
+		let check = CheckBidAffordable::<Test>::new();
+		let balance = Stablecoin::get_balance(1);
+		let price = Perbill::from_percent(80);
+		let affordable = TestCall::Stablecoin(Call::bid_for_bond(price, balance, balance));
+		assert_ok!(check.validate(&1, &affordable, DispatchInfo::default(), 0));
+
+		let quantity = 2 * balance;
+		let unaffordable = TestCall::Stablecoin(Call::bid_for_bond(price, quantity, quantity));
+		assert_eq!(
+			check.validate(&1, &unaffordable, DispatchInfo::default(), 0),
+			InvalidTransaction::Custom(UNAFFORDABLE_BID).into()
+		);
+		assert!(check.clone().pre_dispatch(&1, &unaffordable, DispatchInfo::default(), 0).is_err());
+		let simple = TestCall::Stablecoin(Call::bid_for_bond_simple(price, quantity));
+		assert!(check.validate(&1, &simple, DispatchInfo::default(), 0).is_err());
+
+		// a ladder is checked against its total payment
+		let ladder = |quantity| TestCall::Stablecoin(Call::bid_ladder(vec![(price, quantity); 2]));
+		assert_ok!(check.validate(&1, &ladder(balance / 2), DispatchInfo::default(), 0));
+		assert_eq!(
+			check.validate(&1, &ladder(balance), DispatchInfo::default(), 0),
+			InvalidTransaction::Custom(UNAFFORDABLE_BID).into()
+		);
+		let overflowing = vec![(Perbill::from_percent(100), Coins::max_value()); 2];
+		let overflowing = TestCall::Stablecoin(Call::bid_ladder(overflowing));
+		assert_eq!(
+			check.validate(&1, &overflowing, DispatchInfo::default(), 0),
+			InvalidTransaction::Custom(UNAFFORDABLE_BID).into()
+		);
+		// other calls are left to dispatch
+		let transfer = TestCall::Stablecoin(Call::send_coins(2, quantity));
+		assert_ok!(check.validate(&1, &transfer, DispatchInfo::default(), 0));
+
+		// nothing was executed
+		assert_eq!(Stablecoin::get_balance(1), balance);
+		assert_eq!(Stablecoin::bid_count(), 0);
+	});
+}
+
 // ------------------------------------------------------------
 // bonds
 #[test]
@@ -313,6 +1589,32 @@ fn adding_bonds() {
 	})
 }
 
+#[test]
+fn consuming_front_bond_only_touches_affected_entries() {
+	new_test_ext().execute_with(|| {
+		let payout = BaseUnit::get();
+		for acc in 3..6 {
+			add_bond(Stablecoin::new_bond(acc, payout));
+		}
+		let (start, length) = Stablecoin::bonds_range();
+		assert_eq!(length, 3);
+		let untouched = Stablecoin::get_bond(start.wrapping_add(2));
+
+		// fully consume the front bond and partially consume the second one
+		let amount = payout + payout / 2;
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), amount));
+
+		// the consumed bond is removed and the bounds are moved forward
+		assert!(!<Stablecoin as Store>::Bonds::contains_key(start));
+		assert_eq!(Stablecoin::bonds_range(), (start.wrapping_add(1), 2));
+		// the partially consumed bond is updated in place
+		assert_eq!(Stablecoin::get_bond(start.wrapping_add(1)).payout, payout / 2);
+		// the remaining bond is not rewritten
+		assert_eq!(Stablecoin::get_bond(start.wrapping_add(2)), untouched);
+		assert!(!<Stablecoin as Store>::Bonds::contains_key(start.wrapping_add(3)));
+	});
+}
+
 #[test]
 fn expire_bonds() {
 	new_test_ext_with(vec![1]).execute_with(|| {
@@ -336,6 +1638,8 @@ fn expire_bonds() {
 			prev_acc_balance, acc_balance,
 			"account balance should not change as the bond expired"
 		);
+		// the payout of the expired bond is only recorded, the expansion is not reduced by it
+		assert_eq!(Stablecoin::saved_seigniorage(), payout);
 		assert_eq!(
 			prev_supply + 42,
 			Stablecoin::coin_supply(),
@@ -424,9 +1728,168 @@ fn expire_bonds_and_expand_supply() {
 	});
 }
 
+#[test]
+fn expired_bonds_refund_payment() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		System::set_block_number(1);
+		let bidder = 2;
+		assert_ok!(Stablecoin::transfer_from_to(&1, &bidder, 10 * BaseUnit::get()));
+		let balance_before = Stablecoin::get_balance(bidder);
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(bidder),
+			Perbill::from_percent(80),
+			10 * BaseUnit::get()
+		));
+		let paid = 8 * BaseUnit::get();
+		assert_eq!(Stablecoin::get_balance(bidder), balance_before - paid);
+		assert_ok!(Stablecoin::contract_supply(Stablecoin::coin_supply(), paid));
+		let (start, length) = Stablecoin::bonds_range();
+		assert_eq!(length, 1);
+		assert_eq!(Stablecoin::get_bond(start).paid, paid);
+
+		// pay out a quarter of the bond --> a quarter of the payment is no longer refundable
+		let payout = 10 * BaseUnit::get();
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout / 4));
+		assert_eq!(Stablecoin::get_bond(start).paid, paid - paid / 4);
+
+		System::set_block_number(System::block_number() + ExpirationPeriod::get());
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(supply, 42));
+		assert_eq!(Stablecoin::bonds_range().1, 0);
+		let refund = paid - paid / 4;
+		assert!(stablecoin_events().contains(&RawEvent::BondExpired(bidder, refund)));
+		assert_eq!(Stablecoin::get_balance(bidder), balance_before - paid + payout / 4 + refund);
+		// the refund is added to the supply on top of the expansion
+		assert_eq!(Stablecoin::coin_supply(), supply + refund + 42);
+		assert_coins_accounted();
+	});
+}
+
+#[test]
+fn expired_bond_payouts_are_saved_or_routed_to_treasury() {
+	let payout = 3 * BaseUnit::get();
+	// without a treasury the payouts are only recorded
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		add_bond(Stablecoin::new_bond(1, payout));
+		add_bond(Stablecoin::new_bond(2, payout));
+		System::set_block_number(1 + ExpirationPeriod::get());
+		add_bond(Stablecoin::new_bond(3, payout));
+
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(supply, BaseUnit::get()));
+		let events = stablecoin_events();
+		assert!(events.contains(&RawEvent::BondExpiredUnpaid(1, payout)));
+		assert!(events.contains(&RawEvent::BondExpiredUnpaid(2, payout)));
+		assert_eq!(Stablecoin::saved_seigniorage(), 2 * payout);
+		// the live bond receives the whole expansion
+		assert!(events.contains(&RawEvent::BondReleased(3, BaseUnit::get())));
+		assert_eq!(Stablecoin::coin_supply(), supply + BaseUnit::get());
+		assert_coins_accounted();
+	});
+	// the treasury receives the payouts out of the expansion
+	new_test_ext().execute_with(|| {
+		let treasury = 20;
+		EXPIRED_BOND_TREASURY.with(|v| *v.borrow_mut() = Some(treasury));
+		System::set_block_number(1);
+		add_bond(Stablecoin::new_bond(1, payout));
+		System::set_block_number(1 + ExpirationPeriod::get());
+		add_bond(Stablecoin::new_bond(3, payout));
+
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(supply, payout + BaseUnit::get()));
+		assert!(stablecoin_events().contains(&RawEvent::BondExpiredUnpaid(1, payout)));
+		assert_eq!(Stablecoin::get_balance(treasury), payout);
+		assert_eq!(Stablecoin::saved_seigniorage(), 0);
+		assert!(stablecoin_events().contains(&RawEvent::BondReleased(3, BaseUnit::get())));
+		assert_eq!(Stablecoin::coin_supply(), supply + payout + BaseUnit::get());
+		assert_coins_accounted();
+		EXPIRED_BOND_TREASURY.with(|v| *v.borrow_mut() = None);
+	});
+}
+
+#[test]
+fn compact_bonds_removes_expired_bonds() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		let payout = BaseUnit::get();
+		add_bond(Stablecoin::new_bond(2, payout));
+		add_bond(Stablecoin::new_bond(3, payout));
+		add_bond(Stablecoin::new_bond(4, payout));
+		System::set_block_number(System::block_number() + 10);
+		add_bond(Stablecoin::new_bond(5, payout));
+		add_bond(Stablecoin::new_bond(6, payout));
+
+		// pay out the first bond fully and the second one partially
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout + payout / 2));
+		assert_eq!(Stablecoin::bonds_range().1, 4);
+
+		// expire the bonds created in the first block
+		System::set_block_number(System::block_number() + ExpirationPeriod::get() - 10);
+		assert_ok!(Stablecoin::compact_bonds(Origin::signed(1), 1));
+		assert_eq!(Stablecoin::bonds_range().1, 3);
+		assert_ok!(Stablecoin::compact_bonds(Origin::signed(1), 10));
+
+		let accounts: Vec<AccountId> = Stablecoin::bonds().map(|b| b.account).collect();
+		assert_eq!(accounts, vec![5, 6]);
+		for bond in Stablecoin::bonds() {
+			assert_gt!(bond.expiration, System::block_number());
+		}
+	});
+}
+
+#[test]
+fn compact_bonds_refunds_within_supply_ceiling() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let quantity = 2 * BaseUnit::get();
+		let (price_2, price_3) = (Perbill::from_percent(80), Perbill::from_percent(50));
+		let (paid_2, paid_3) = (price_2 * quantity, price_3 * quantity);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), price_2, quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(3), price_3, quantity));
+		assert_ok!(Stablecoin::contract_supply(Stablecoin::coin_supply(), paid_2 + paid_3));
+		let paid: Vec<(AccountId, Coins)> = Stablecoin::bonds().map(|b| (b.account, b.paid)).collect();
+		assert_eq!(paid, vec![(2, paid_2), (3, paid_3)]);
+
+		System::set_block_number(1 + ExpirationPeriod::get());
+		let supply = Stablecoin::coin_supply();
+		let (balance_2, balance_3) = (Stablecoin::get_balance(2), Stablecoin::get_balance(3));
+		// only the refund of the second bond fits below the ceiling
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = supply + paid_3);
+		assert_ok!(Stablecoin::compact_bonds(Origin::signed(1), 10));
+		assert_eq!(Stablecoin::get_balance(2), balance_2);
+		assert_eq!(Stablecoin::get_balance(3), balance_3 + paid_3);
+		assert_eq!(Stablecoin::coin_supply(), supply + paid_3);
+		let accounts: Vec<AccountId> = Stablecoin::bonds().map(|b| b.account).collect();
+		assert_eq!(accounts, vec![2]);
+		let events = stablecoin_events();
+		assert!(events.contains(&RawEvent::BondExpired(3, paid_3)));
+		assert!(events.contains(&RawEvent::BondsCompacted(1)));
+		assert_coins_accounted();
+
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = Coins::max_value());
+		assert_ok!(Stablecoin::compact_bonds(Origin::signed(1), 10));
+		assert_eq!(Stablecoin::get_balance(2), balance_2 + paid_2);
+		assert_eq!(Stablecoin::coin_supply(), supply + paid_2 + paid_3);
+		assert_eq!(Stablecoin::bonds_range().1, 0);
+		assert_coins_accounted();
+	});
+}
+
 // ------------------------------------------------------------
 // handout tests
 
+#[test]
+fn handout_rejects_share_supply_overflow() {
+	new_test_ext().execute_with(|| {
+		let shares = vec![(1, u64::max_value() - 1), (2, 1), (3, 1)];
+		let supply = Stablecoin::coin_supply();
+		assert_noop!(
+			Stablecoin::hand_out_coins(&shares, 1000, supply),
+			Error::<Test>::ShareSupplyOverflow
+		);
+	});
+}
+
 #[test]
 fn simple_handout_test() {
 	new_test_ext().execute_with(|| {
@@ -499,6 +1962,258 @@ fn handout_more_than_shares_test() {
 	});
 }
 
+#[test]
+fn handout_emits_deposit_events() {
+	new_test_ext_with(vec![1, 2, 3]).execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Stablecoin::hand_out_coins(
+			&Stablecoin::shares(),
+			7,
+			Stablecoin::coin_supply()
+		));
+
+		assert_eq!(
+			stablecoin_events(),
+			vec![
+				RawEvent::Deposit(1, 3),
+				RawEvent::Deposit(2, 2),
+				RawEvent::Deposit(3, 2),
+			]
+		);
+	});
+}
+
+#[test]
+fn handout_skips_shareholders_below_minimum_balance() {
+	new_test_ext_with(vec![1, 2, 3, 4]).execute_with(|| {
+		let balance_per_acc = InitialSupply::get() / 4;
+		assert_ok!(Stablecoin::transfer_from_to(&1, &2, balance_per_acc - BaseUnit::get()));
+		assert_ok!(Stablecoin::transfer_from_to(&3, &4, balance_per_acc / 2));
+		MIN_SEIGNIORAGE_BALANCE.with(|v| *v.borrow_mut() = balance_per_acc / 2);
+		let balances_before: Vec<Coins> = (1..=4).map(Stablecoin::get_balance).collect();
+		let supply = Stablecoin::coin_supply();
+
+		let amount = 31;
+		assert_ok!(Stablecoin::hand_out_coins(&Stablecoin::shares(), amount, supply));
+
+		let payouts: Vec<Coins> = (1..=4)
+			.map(Stablecoin::get_balance)
+			.zip(balances_before)
+			.map(|(after, before)| after - before)
+			.collect();
+		// 1 is below the minimum, 3 is exactly at the minimum
+		assert_eq!(payouts, vec![0, 11, 10, 10]);
+		assert_eq!(payouts.iter().sum::<Coins>(), amount);
+		assert_eq!(Stablecoin::coin_supply(), supply + amount);
+	});
+}
+
+#[test]
+fn handout_caps_share_of_expansion() {
+	new_test_ext_with(vec![1, 2, 3]).execute_with(|| {
+		let shares = vec![(1, 8), (2, 1), (3, 1)];
+		let payouts = |amount: Coins| -> Vec<Coins> {
+			let balances_before: Vec<Coins> = (1..=3).map(Stablecoin::get_balance).collect();
+			assert_ok!(Stablecoin::hand_out_coins(&shares, amount, Stablecoin::coin_supply()));
+			(1..=3)
+				.map(Stablecoin::get_balance)
+				.zip(balances_before)
+				.map(|(after, before)| after - before)
+				.collect()
+		};
+
+		MAX_SHARE_OF_EXPANSION.with(|v| *v.borrow_mut() = Perbill::from_percent(50));
+		// the excess of the majority shareholder goes to the minority shareholders
+		assert_eq!(payouts(100), vec![50, 25, 25]);
+		assert_eq!(payouts(1000), vec![500, 250, 250]);
+
+		// the cap is not applied if the shareholders cannot take the whole amount with it
+		MAX_SHARE_OF_EXPANSION.with(|v| *v.borrow_mut() = Perbill::from_percent(20));
+		assert_eq!(payouts(100), vec![80, 10, 10]);
+		MAX_SHARE_OF_EXPANSION.with(|v| *v.borrow_mut() = Perbill::one());
+	});
+}
+
+#[test]
+fn renounce_shares_increases_handout_of_others() {
+	new_test_ext_with(vec![1, 2, 3]).execute_with(|| {
+		<Shares<Test>>::put(vec![(1, 2), (2, 1), (3, 1)]);
+
+		assert_noop!(
+			Stablecoin::renounce_shares(Origin::signed(4), 1),
+			Error::<Test>::InsufficientShares
+		);
+		assert_noop!(
+			Stablecoin::renounce_shares(Origin::signed(2), 2),
+			Error::<Test>::InsufficientShares
+		);
+
+		assert_ok!(Stablecoin::renounce_shares(Origin::signed(1), 1));
+		assert_eq!(Stablecoin::shares(), vec![(1, 1), (2, 1), (3, 1)]);
+		assert_ok!(Stablecoin::renounce_shares(Origin::signed(3), 1));
+		assert_eq!(Stablecoin::shares(), vec![(1, 1), (2, 1)]);
+
+		let balances_before: Vec<Coins> = (1..=3).map(Stablecoin::get_balance).collect();
+		let amount = 10 * BaseUnit::get();
+		assert_ok!(Stablecoin::hand_out_coins(
+			&Stablecoin::shares(),
+			amount,
+			Stablecoin::coin_supply()
+		));
+		let payouts: Vec<Coins> = (1..=3)
+			.map(Stablecoin::get_balance)
+			.zip(balances_before)
+			.map(|(after, before)| after - before)
+			.collect();
+		assert_eq!(payouts, vec![amount / 2, amount / 2, 0]);
+	});
+}
+
+#[test]
+fn renounce_shares_rejects_zero_amount() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			Stablecoin::renounce_shares(Origin::signed(1), 0),
+			Error::<Test>::ZeroAmount
+		);
+		assert!(stablecoin_events().is_empty());
+	});
+}
+
+#[test]
+fn handout_remainder_quickcheck() {
+	fn property(share_counts: Vec<u8>, amount: Coins) -> TestResult {
+		if share_counts.is_empty() || share_counts.len() > 100 || amount == 0 {
+			return TestResult::discard();
+		}
+		if share_counts.iter().all(|s| *s == 0) {
+			return TestResult::discard();
+		}
+		let shares: Vec<(AccountId, u64)> = share_counts
+			.iter()
+			.enumerate()
+			.map(|(i, s)| (i as AccountId + 1, *s as u64))
+			.collect();
+		let share_supply: u64 = shares.iter().map(|(_a, s)| s).sum();
+
+		new_test_ext().execute_with(|| {
+			let supply = Stablecoin::coin_supply();
+			if amount > Coins::max_value() - supply {
+				return TestResult::discard();
+			}
+			let balances_before: Vec<Coins> =
+				shares.iter().map(|(a, _s)| Stablecoin::get_balance(a)).collect();
+			assert_ok!(Stablecoin::hand_out_coins(&shares, amount, supply));
+
+			let mut total_paid: u128 = 0;
+			for ((acc, num_shares), before) in shares.iter().zip(balances_before) {
+				let paid = Stablecoin::get_balance(acc) - before;
+				let proportional = (amount as u128 * *num_shares as u128 / share_supply as u128) as Coins;
+				assert_ge!(paid, proportional);
+				assert_le!(paid, proportional + 1);
+				total_paid += paid as u128;
+			}
+			assert_eq!(total_paid, amount as u128);
+			assert_eq!(Stablecoin::coin_supply(), supply + amount);
+
+			TestResult::passed()
+		})
+	}
+
+	seeded_quickcheck()
+		.min_tests_passed(5)
+		.tests(50)
+		.max_tests(500)
+		.quickcheck(property as fn(Vec<u8>, Coins) -> TestResult)
+}
+
+#[test]
+fn transfer_shares_moves_handouts() {
+	new_test_ext_with(vec![1, 2, 3]).execute_with(|| {
+		System::set_block_number(1);
+		<Shares<Test>>::put(vec![(1, 2), (2, 1), (3, 1)]);
+
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(4), 1, 1),
+			Error::<Test>::InsufficientShares
+		);
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(2), 1, 2),
+			Error::<Test>::InsufficientShares
+		);
+
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(1), 4, 1));
+		assert_eq!(Stablecoin::shares(), vec![(1, 1), (2, 1), (3, 1), (4, 1)]);
+		// the sender is removed once all shares are transferred
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(1), 4, 1));
+		assert_eq!(Stablecoin::shares(), vec![(2, 1), (3, 1), (4, 2)]);
+		assert_eq!(Stablecoin::checked_share_supply(&Stablecoin::shares()), Ok(4));
+		assert!(stablecoin_events().contains(&RawEvent::SharesTransferred(1, 4, 1)));
+		// transfers to oneself do not change the shares
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(4), 4, 2));
+		assert_eq!(Stablecoin::shares(), vec![(2, 1), (3, 1), (4, 2)]);
+
+		let balances_before: Vec<Coins> = (1..=4).map(Stablecoin::get_balance).collect();
+		let amount = 4 * BaseUnit::get();
+		assert_ok!(Stablecoin::hand_out_coins(
+			&Stablecoin::shares(),
+			amount,
+			Stablecoin::coin_supply()
+		));
+		let payouts: Vec<Coins> = (1..=4)
+			.map(Stablecoin::get_balance)
+			.zip(balances_before)
+			.map(|(after, before)| after - before)
+			.collect();
+		assert_eq!(payouts, vec![0, amount / 4, amount / 4, amount / 2]);
+	});
+}
+
+#[test]
+fn transfer_shares_rejects_zero_amount() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		<Shares<Test>>::put(vec![(1, 1), (2, 1)]);
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(1), 3, 0),
+			Error::<Test>::ZeroAmount
+		);
+		// not even accounts without shares can add zero share entries
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(4), 3, 0),
+			Error::<Test>::ZeroAmount
+		);
+		assert_eq!(Stablecoin::shares(), vec![(1, 1), (2, 1)]);
+	});
+}
+
+#[test]
+fn share_transfer_cooldown_test() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		SHARE_TRANSFER_COOLDOWN.with(|v| *v.borrow_mut() = 3);
+		<Shares<Test>>::put(vec![(1, 3), (2, 1)]);
+		System::set_block_number(1);
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(1), 3, 1));
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(1), 3, 1),
+			Error::<Test>::ShareTransferOnCooldown
+		);
+		// other accounts (including the receiver) are not affected
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(2), 1, 1));
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(3), 2, 1));
+
+		System::set_block_number(3);
+		assert_noop!(
+			Stablecoin::transfer_shares(Origin::signed(1), 3, 1),
+			Error::<Test>::ShareTransferOnCooldown
+		);
+		System::set_block_number(4);
+		assert_ok!(Stablecoin::transfer_shares(Origin::signed(1), 3, 1));
+		assert_eq!(Stablecoin::shares(), vec![(1, 2), (2, 1), (3, 1)]);
+		SHARE_TRANSFER_COOLDOWN.with(|v| *v.borrow_mut() = 0);
+	});
+}
+
 #[test]
 fn handout_quickcheck() {
 	fn property(shareholders: Vec<AccountId>, amount: Coins) -> TestResult {
@@ -542,13 +2257,64 @@ fn handout_quickcheck() {
 		})
 	}
 
-	QuickCheck::new()
+	seeded_quickcheck()
 		.min_tests_passed(5)
 		.tests(50)
 		.max_tests(500)
 		.quickcheck(property as fn(Vec<u64>, u64) -> TestResult)
 }
 
+#[test]
+fn handout_with_zero_share_supply_fails() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		<Shares<Test>>::put(vec![(1, 0)]);
+		assert_noop!(
+			Stablecoin::hand_out_coins(&Stablecoin::shares(), 42, Stablecoin::coin_supply()),
+			Error::<Test>::ZeroShareSupply
+		);
+
+		<Shares<Test>>::put(Vec::<(AccountId, u64)>::new());
+		assert_noop!(
+			Stablecoin::hand_out_coins(&Stablecoin::shares(), 42, Stablecoin::coin_supply()),
+			Error::<Test>::ZeroShareSupply
+		);
+
+	});
+}
+
+#[test]
+fn expand_with_zero_share_supply_uses_fallback() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		<Shares<Test>>::put(Vec::<(AccountId, u64)>::new());
+
+		// without a fallback account the coins are not created
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(prev_supply, 42));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply);
+
+		// the fallback account receives the coins
+		SEIGNIORAGE_FALLBACK.with(|v| *v.borrow_mut() = Some(99));
+		let prev_balance = Stablecoin::get_balance(99);
+		assert_ok!(Stablecoin::expand_supply(prev_supply, 42));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply + 42);
+		assert_eq!(Stablecoin::get_balance(99), prev_balance + 42);
+		SEIGNIORAGE_FALLBACK.with(|v| *v.borrow_mut() = None);
+	});
+}
+
+#[test]
+fn rebase_without_shareholders_does_not_panic() {
+	new_test_ext_with(vec![1]).execute_with(|| {
+		let supply = Stablecoin::coin_supply();
+		for shares in vec![vec![], vec![(1, 0), (2, 0)]] {
+			<Shares<Test>>::put(shares);
+			// price too low --> expand, but there is nobody to hand out the Coins to
+			assert_ok!(Stablecoin::on_block_with_price(AdjustmentFrequency::get(), TEST_BASE_UNIT / 2));
+			assert_eq!(Stablecoin::coin_supply(), supply);
+		}
+	});
+}
+
 // ------------------------------------------------------------
 // expand and contract tests
 #[test]
@@ -564,6 +2330,7 @@ fn expand_supply_test() {
 		let prev_supply = Stablecoin::coin_supply();
 		let amount = 13 * BaseUnit::get();
 		assert_ok!(Stablecoin::expand_supply(prev_supply, amount));
+		assert_coins_accounted();
 
 		let amount_per_acc = InitialSupply::get() / 10 + BaseUnit::get() / 10;
 		assert_eq!(Stablecoin::get_balance(1), amount_per_acc);
@@ -583,42 +2350,792 @@ fn expand_supply_test() {
 }
 
 #[test]
-fn contract_supply_test() {
+fn bonds_are_linked_to_their_bids() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let quantity = 2 * BaseUnit::get();
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(80), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(60), quantity));
+
+		let prev_supply = Stablecoin::coin_supply();
+		// fill the bid of account 2 fully and the one of account 3 partially
+		let amount = Perbill::from_percent(80) * quantity + BaseUnit::get() / 2;
+		assert_ok!(Stablecoin::contract_supply(prev_supply, amount));
+
+		let bonds: Vec<(AccountId, u64)> = Stablecoin::bonds().map(|b| (b.account, b.bid_sequence)).collect();
+		assert_eq!(bonds, vec![(2, 1), (3, 2)]);
+
+		let events = stablecoin_events();
+		for bond in Stablecoin::bonds() {
+			assert!(events.contains(&RawEvent::NewBond(
+				bond.account,
+				bond.payout,
+				bond.expiration,
+				bond.bid_sequence
+			)));
+		}
+		assert!(events.contains(&RawEvent::BidFilled(2, 1, Perbill::from_percent(80) * quantity)));
+		assert!(events.contains(&RawEvent::BidFilled(3, 2, BaseUnit::get() / 2)));
+	});
+}
+
+#[test]
+fn contract_supply_burns_from_reserve_without_bids() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		System::set_block_number(1);
+		let reserve_balance = Stablecoin::get_balance(2);
+		let prev_supply = Stablecoin::coin_supply();
+
+		// disabled by default
+		assert_eq!(Stablecoin::contract_supply(prev_supply, BaseUnit::get()), Ok(0));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply);
+
+		CONTRACTION_RESERVE.with(|v| *v.borrow_mut() = Some(2));
+		let amount = 3 * BaseUnit::get();
+		assert_eq!(Stablecoin::contract_supply(prev_supply, amount), Ok(amount));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply - amount);
+		assert_eq!(Stablecoin::get_balance(2), reserve_balance - amount);
+		assert!(stablecoin_events().contains(&RawEvent::DirectContraction(amount)));
+
+		// limited by the balance of the reserve
+		let supply = Stablecoin::coin_supply();
+		let rest = Stablecoin::get_balance(2);
+		assert_eq!(Stablecoin::contract_supply(supply, rest + BaseUnit::get()), Ok(rest));
+		assert_eq!(Stablecoin::get_balance(2), 0);
+		assert_eq!(Stablecoin::coin_supply(), supply - rest);
+		CONTRACTION_RESERVE.with(|v| *v.borrow_mut() = None);
+	});
+}
+
+#[test]
+fn perpetual_bonds_do_not_expire() {
+	new_test_ext().execute_with(|| {
+		BONDS_EXPIRE.with(|v| *v.borrow_mut() = false);
+		let payout = 3 * BaseUnit::get();
+		add_bond(Stablecoin::new_bond(1, payout));
+		let balance = Stablecoin::get_balance(1);
+
+		System::set_block_number(System::block_number() + 10 * ExpirationPeriod::get());
+		assert_eq!(Stablecoin::remove_expired_bonds(10), 0);
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout));
+		assert_eq!(Stablecoin::get_balance(1), balance + payout);
+		assert_eq!(Stablecoin::bond_count(), 0);
+		BONDS_EXPIRE.with(|v| *v.borrow_mut() = true);
+	});
+}
+
+#[test]
+fn bond_payout_events() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		add_bond(Stablecoin::new_bond(1, 3 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(2, 5 * BaseUnit::get()));
+
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(prev_supply, 4 * BaseUnit::get()));
+
+		let events = stablecoin_events();
+		assert!(events.contains(&RawEvent::BondFulfilled(1, 3 * BaseUnit::get())));
+		assert!(events.contains(&RawEvent::BondPartiallyFulfilled(2, 4 * BaseUnit::get())));
+		assert_eq!(Stablecoin::bonds().map(|b| b.payout).collect::<Vec<_>>(), vec![4 * BaseUnit::get()]);
+		// the payed out amounts
+		let released: Vec<_> = events
+			.into_iter()
+			.filter(|e| matches!(e, RawEvent::BondReleased(..)))
+			.collect();
+		assert_eq!(
+			released,
+			vec![
+				RawEvent::BondReleased(1, 3 * BaseUnit::get()),
+				RawEvent::BondReleased(2, BaseUnit::get()),
+			]
+		);
+	});
+}
+
+#[test]
+fn bonds_of_filters_by_account() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::bonds_of(&1), vec![]);
+		add_bond(Stablecoin::new_bond(1, 3 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(2, 5 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(1, 2 * BaseUnit::get()));
+
+		let payouts = |who| Stablecoin::bonds_of(&who).iter().map(|b| b.payout).collect::<Vec<_>>();
+		assert_eq!(payouts(1), vec![3 * BaseUnit::get(), 2 * BaseUnit::get()]);
+		assert_eq!(payouts(2), vec![5 * BaseUnit::get()]);
+		assert_eq!(payouts(3), vec![]);
+	});
+}
+
+#[test]
+fn pro_rata_bond_payout() {
+	new_test_ext().execute_with(|| {
+		BOND_PAYOUT_MODE.with(|v| *v.borrow_mut() = BondPayoutMode::ProRata);
+		let payouts = [2 * BaseUnit::get(), 4 * BaseUnit::get(), 6 * BaseUnit::get()];
+		for (acc, payout) in (1..).zip(payouts.iter()) {
+			add_bond(Stablecoin::new_bond(acc, *payout));
+		}
+		let balances: Vec<Coins> = (1..=3).map(Stablecoin::get_balance).collect();
+		let total: Coins = payouts.iter().sum();
+
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(prev_supply, total / 2));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply + total / 2);
+
+		// every bond was payed out halfway
+		let bonds: Vec<BondT> = Stablecoin::bonds().collect();
+		assert_eq!(bonds.len(), 3);
+		for (i, payout) in payouts.iter().enumerate() {
+			assert_eq!(bonds[i].payout, payout / 2);
+			assert_eq!(Stablecoin::get_balance(i as AccountId + 1), balances[i] + payout / 2);
+		}
+
+		// an amount covering all bonds pays them out completely
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), total / 2));
+		assert_eq!(Stablecoin::bond_count(), 0);
+		for (i, payout) in payouts.iter().enumerate() {
+			assert_eq!(Stablecoin::get_balance(i as AccountId + 1), balances[i] + payout);
+		}
+		BOND_PAYOUT_MODE.with(|v| *v.borrow_mut() = BondPayoutMode::Fifo);
+	});
+}
+
+#[test]
+fn auto_rebid_bids_bond_payouts() {
+	new_test_ext().execute_with(|| {
+		let price = Perbill::from_percent(80);
+		assert_noop!(
+			Stablecoin::set_auto_rebid(Origin::signed(2), Some(Perbill::from_percent(5))),
+			Error::<Test>::BondPriceTooLow
+		);
+		assert_ok!(Stablecoin::set_auto_rebid(Origin::signed(2), Some(price)));
+		let payout = 4 * BaseUnit::get();
+		add_bond(Stablecoin::new_bond(2, payout));
+		add_bond(Stablecoin::new_bond(3, payout));
+
+		let balance_2 = Stablecoin::get_balance(2);
+		let balance_3 = Stablecoin::get_balance(3);
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_supply(prev_supply, 2 * payout));
+
+		// the payout was bid instead of credited
+		assert_eq!(Stablecoin::get_balance(2), balance_2);
+		let bids = Stablecoin::bids_of(&2);
+		assert_eq!(bids.len(), 1);
+		assert_eq!(bids[0].price, price);
+		assert_eq!(bids[0].quantity, 5 * BaseUnit::get());
+		assert_eq!(bids[0].payment(), payout);
+		// other accounts are credited as usual
+		assert_eq!(Stablecoin::get_balance(3), balance_3 + payout);
+		assert_eq!(Stablecoin::coin_supply(), prev_supply + 2 * payout);
+
+		// disabling credits the balance again
+		assert_ok!(Stablecoin::set_auto_rebid(Origin::signed(2), None));
+		add_bond(Stablecoin::new_bond(2, payout));
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout));
+		assert_eq!(Stablecoin::get_balance(2), balance_2 + payout);
+	});
+}
+
+#[test]
+fn auto_rebid_respects_bid_limits() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let price = Perbill::from_percent(80);
+		let payout = 4 * BaseUnit::get();
+		assert_ok!(Stablecoin::set_auto_rebid(Origin::signed(2), Some(price)));
+
+		// the bid intake has no room for the rebid --> credited without using a bid sequence number
+		MAX_BID_INTAKE_PER_BLOCK.with(|v| *v.borrow_mut() = payout - 1);
+		add_bond(Stablecoin::new_bond(2, payout));
+		let balance = Stablecoin::get_balance(2);
+		let sequence = Stablecoin::bid_sequence();
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout));
+		assert_eq!(Stablecoin::get_balance(2), balance + payout);
+		assert!(Stablecoin::bids_of(&2).is_empty());
+		assert_eq!(Stablecoin::bid_sequence(), sequence);
+		MAX_BID_INTAKE_PER_BLOCK.with(|v| *v.borrow_mut() = Coins::max_value());
+
+		// the rebid counts towards the intake and starts the cooldown
+		BID_COOLDOWN.with(|v| *v.borrow_mut() = 3);
+		add_bond(Stablecoin::new_bond(2, payout));
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout));
+		assert_eq!(Stablecoin::bids_of(&2).len(), 1);
+		assert_eq!(Stablecoin::bid_intake(), (1, payout));
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(2), price, BaseUnit::get()),
+			Error::<Test>::BidOnCooldown
+		);
+		// a rebid on cooldown is credited as well
+		let balance = Stablecoin::get_balance(2);
+		add_bond(Stablecoin::new_bond(2, payout));
+		assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), payout));
+		assert_eq!(Stablecoin::get_balance(2), balance + payout);
+		assert_eq!(Stablecoin::bids_of(&2).len(), 1);
+		assert_coins_accounted();
+		BID_COOLDOWN.with(|v| *v.borrow_mut() = 0);
+	});
+}
+
+#[test]
+fn expansion_is_capped_at_max_coin_supply() {
+	new_test_ext().execute_with(|| {
+		let supply = Stablecoin::coin_supply();
+		let ceiling = supply + 3 * BaseUnit::get();
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = ceiling);
+		add_bond(Stablecoin::new_bond(1, 2 * BaseUnit::get()));
+
+		assert_ok!(Stablecoin::expand_supply(supply, 5 * BaseUnit::get()));
+		assert_eq!(Stablecoin::coin_supply(), ceiling);
+		assert_eq!(Stablecoin::bond_count(), 0);
+
+		// no more expansion at the ceiling
+		assert_ok!(Stablecoin::expand_supply(ceiling, BaseUnit::get()));
+		assert_eq!(Stablecoin::coin_supply(), ceiling);
+		assert_noop!(
+			Stablecoin::hand_out_coins(&Stablecoin::shares(), BaseUnit::get(), ceiling),
+			Error::<Test>::SupplyCeilingReached
+		);
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn expansion_near_max_supply_does_not_overflow() {
+	new_test_ext().execute_with(|| {
+		let supply = Coins::max_value() - 10;
+		<CoinSupply>::put(supply);
+		assert_noop!(Stablecoin::expand_supply(supply, 11), Error::<Test>::CoinSupplyOverflow);
+		assert_noop!(
+			Stablecoin::hand_out_coins(&Stablecoin::shares(), 11, supply),
+			Error::<Test>::CoinSupplyOverflow
+		);
+
+		// the refund of an expired bond would exceed the supply on top of the expansion
+		System::set_block_number(1);
+		add_bond(BondT {
+			paid: 5,
+			..Stablecoin::new_bond(1, 20)
+		});
+		System::set_block_number(1 + ExpirationPeriod::get());
+		add_bond(Stablecoin::new_bond(2, 20));
+		assert_ok!(Stablecoin::expand_supply(supply, 10));
+		assert_eq!(Stablecoin::coin_supply(), Coins::max_value());
+		// the expired bond is kept instead of being refunded
+		assert_eq!(Stablecoin::bonds_of(&1).len(), 1);
+	});
+}
+
+#[test]
+fn expired_bond_refunds_respect_supply_ceiling() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		add_bond(BondT {
+			paid: 5,
+			..Stablecoin::new_bond(1, 20)
+		});
+		System::set_block_number(1 + ExpirationPeriod::get());
+		let supply = Stablecoin::coin_supply();
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = supply + 10);
+		// the expansion reaches the ceiling so there is no room for the refund
+		assert_ok!(Stablecoin::expand_supply(supply, 10));
+		assert_eq!(Stablecoin::coin_supply(), supply + 10);
+		assert_eq!(Stablecoin::bonds_of(&1).len(), 1);
+		assert_coins_accounted();
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn deferred_expired_bond_refund_does_not_block_live_bonds() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		add_bond(BondT {
+			paid: 5,
+			..Stablecoin::new_bond(1, 20)
+		});
+		System::set_block_number(50);
+		add_bond(Stablecoin::new_bond(2, 10));
+		System::set_block_number(1 + ExpirationPeriod::get());
+		let supply = Stablecoin::coin_supply();
+		let balance_before = Stablecoin::get_balance(2);
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = supply + 10);
+		// no room for the refund, but the live bond behind the expired one is still payed out
+		assert_ok!(Stablecoin::expand_supply(supply, 10));
+		assert_eq!(Stablecoin::get_balance(2), balance_before + 10);
+		assert!(Stablecoin::bonds_of(&2).is_empty());
+		assert_eq!(Stablecoin::coin_supply(), supply + 10);
+		// the expired bond is kept at the front for a later expansion
+		let accounts: Vec<AccountId> = Stablecoin::bonds().map(|b| b.account).collect();
+		assert_eq!(accounts, vec![1]);
+		assert_coins_accounted();
+		MAX_COIN_SUPPLY.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn expand_supply_conserves_coins_quickcheck() {
+	fn property(bonds: Vec<(AccountId, Coins)>, amount: Coins) -> TestResult {
+		let accounts = 1..=20;
+		let bonds: Vec<(AccountId, Coins)> = bonds
+			.into_iter()
+			.map(|(acc, payout)| (acc % 20 + 1, payout % (10 * BaseUnit::get()) + 1))
+			.collect();
+		let amount = amount % (100 * BaseUnit::get());
+		new_test_ext().execute_with(|| {
+			for (acc, payout) in bonds.iter() {
+				add_bond(Stablecoin::new_bond(*acc, *payout));
+			}
+			let total_balance = || -> Coins { accounts.clone().map(Stablecoin::get_balance).sum() };
+			let total_payouts = || -> Coins { Stablecoin::bonds().map(|b| b.payout).sum() };
+			let balance_before = total_balance();
+			let payouts_before = total_payouts();
+			let supply_before = Stablecoin::coin_supply();
+
+			assert_ok!(Stablecoin::expand_supply(supply_before, amount));
+
+			assert_eq!(Stablecoin::coin_supply(), supply_before + amount);
+			assert_eq!(total_balance(), balance_before + amount);
+			// bonds are payed out first
+			assert_eq!(total_payouts(), payouts_before - min(amount, payouts_before));
+
+			TestResult::passed()
+		})
+	}
+
+	seeded_quickcheck()
+		.min_tests_passed(5)
+		.tests(50)
+		.max_tests(500)
+		.quickcheck(property as fn(Vec<(AccountId, Coins)>, Coins) -> TestResult)
+}
+
+#[test]
+fn partially_filled_bid_keeps_its_place_among_equal_bids() {
+	new_test_ext().execute_with(|| {
+		let price = Perbill::from_percent(80);
+		let quantity = 2 * BaseUnit::get();
+		for account in 1..=3 {
+			Stablecoin::add_bid(Stablecoin::new_bid(account, price, quantity));
+		}
+		let order = |bids: Vec<Bid<AccountId, BlockNumber>>| -> Vec<(AccountId, u64)> {
+			bids.into_iter()
+				.map(|Bid { account, sequence, .. }| (account, sequence))
+				.collect()
+		};
+		// the earliest bid is at the end of the queue to be popped first
+		assert_eq!(order(Stablecoin::bond_bids()), vec![(3, 2), (2, 1), (1, 0)]);
+
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::contract_supply(prev_supply, BaseUnit::get() / 2));
+
+		let bids = Stablecoin::bond_bids();
+		assert_lt!(bids[2].quantity, quantity, "first bid should have been partially filled");
+		assert_eq!(order(bids), vec![(3, 2), (2, 1), (1, 0)]);
+	});
+}
+
+#[test]
+fn contract_supply_test() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let bond_amount = Ratio::new(125, 100)
+			.checked_mul(&BaseUnit::get().into())
+			.map(|r| r.to_integer())
+			.expect("bond_amount should not have overflowed");
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_percent(80),
+			bond_amount
+		));
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(2),
+			Perbill::from_percent(75),
+			2 * BaseUnit::get()
+		));
+		assert_coins_accounted();
+
+		let prev_supply = Stablecoin::coin_supply();
+		let amount = 2 * BaseUnit::get();
+		assert_ok!(Stablecoin::contract_supply(prev_supply, amount));
+		assert_coins_accounted();
+
+		let bids = Stablecoin::bond_bids();
+		assert_eq!(bids.len(), 1, "exactly one bid should have been removed");
+		let remainging_bid_quantity = Fixed64::from_rational(667, 1_000)
+			.saturated_multiply_accumulate(BaseUnit::get())
+			- BaseUnit::get();
+		assert_eq!(bids[0].account, 2);
+		assert_eq!(bids[0].price, Perbill::from_percent(75));
+		assert_eq!(bids[0].quantity, remainging_bid_quantity);
+
+		let (start, _) = Stablecoin::bonds_range();
+		assert_eq!(Stablecoin::get_bond(start).payout, bond_amount);
+		assert_eq!(
+			Stablecoin::get_bond(start + 1).payout,
+			Fixed64::from_rational(333, 1_000).saturated_multiply_accumulate(BaseUnit::get())
+		);
+		// every bond is announced with its account, payout, expiration and bid
+		let expiration = 1 + ExpirationPeriod::get();
+		let new_bonds: Vec<_> = stablecoin_events()
+			.into_iter()
+			.filter(|e| matches!(e, RawEvent::NewBond(..)))
+			.collect();
+		assert_eq!(
+			new_bonds,
+			vec![
+				RawEvent::NewBond(1, bond_amount, expiration, 0),
+				RawEvent::NewBond(2, Stablecoin::get_bond(start + 1).payout, expiration, 1),
+			]
+		);
+
+		assert_eq!(
+			Stablecoin::coin_supply(),
+			prev_supply - amount,
+			"supply should be decreased by amount"
+		);
+	})
+}
+
+#[test]
+fn contract_supply_splits_large_bonds() {
+	new_test_ext().execute_with(|| {
+		let max_size = 2 * BaseUnit::get();
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = max_size);
+		let bid = Stablecoin::new_bid(1, Perbill::from_percent(50), 20 * BaseUnit::get());
+		let sequence = bid.sequence;
+		Stablecoin::add_bid(bid);
+
+		// buys a payout of 9 BaseUnits
+		assert_ok!(Stablecoin::contract_supply(Stablecoin::coin_supply(), 9 * BaseUnit::get() / 2));
+		let bonds: Vec<BondT> = Stablecoin::bonds().collect();
+		assert_eq!(bonds.len(), 5);
+		assert!(bonds.iter().all(|b| b.payout <= max_size && b.account == 1));
+		assert!(bonds.iter().all(|b| b.bid_sequence == sequence));
+		assert_eq!(bonds.iter().map(|b| b.payout).sum::<Coins>(), 9 * BaseUnit::get());
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn bids_split_into_sub_minimum_bonds_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let max_size = 2 * BaseUnit::get();
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = max_size);
+		let price = Perbill::from_percent(80);
+
+		// passes the `BaseUnit` check but the last bond would only have a payout of half a `BaseUnit`
+		let quantity = 5 * BaseUnit::get() / 2;
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), price, quantity),
+			Error::<Test>::BondQuantityTooLow
+		);
+		// splits evenly or leaves a remainder of at least `BaseUnit`
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, 4 * BaseUnit::get()));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, 3 * BaseUnit::get()));
+
+		// no bid can be placed if every bond would be too small
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = BaseUnit::get() / 2);
+		assert_noop!(
+			Stablecoin::bid_for_bond_simple(Origin::signed(1), price, 2 * BaseUnit::get()),
+			Error::<Test>::BondQuantityTooLow
+		);
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn contract_supply_partially_filling_front_bid_burns_exactly_amount() {
+	new_test_ext().execute_with(|| {
+		let price = Perbill::from_percent(80);
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(1), price, 5 * BaseUnit::get()));
+		let payment = price * (5 * BaseUnit::get());
+		let prev_supply = Stablecoin::coin_supply();
+		let prev_escrow = Stablecoin::bid_escrow();
+
+		let amount = BaseUnit::get();
+		assert!(amount < payment);
+		assert_eq!(Stablecoin::contract_supply(prev_supply, amount), Ok(amount));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply - amount);
+		assert_eq!(Stablecoin::bid_escrow(), prev_escrow - amount);
+		assert_coins_accounted();
+		// the rest of the bid stays in the queue
+		let bids = Stablecoin::bond_bids();
+		assert_eq!(bids.len(), 1);
+		assert_eq!(bids[0].payment(), payment - amount);
+	});
+}
+
+#[test]
+fn contract_supply_does_not_exceed_max_bonds() {
+	new_test_ext().execute_with(|| {
+		MAX_BONDS.with(|v| *v.borrow_mut() = 3);
+		add_bond(Stablecoin::new_bond(1, BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(2, BaseUnit::get()));
+		let price = Perbill::from_percent(80);
+		Stablecoin::add_bid(Stablecoin::new_bid(3, price, BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(4, Perbill::from_percent(70), BaseUnit::get()));
+
+		// only the best bid fits into the bond queue
+		let supply = Stablecoin::coin_supply();
+		assert_eq!(Stablecoin::contract_supply(supply, 2 * BaseUnit::get()), Ok(price * BaseUnit::get()));
+		assert_eq!(Stablecoin::bond_count(), 3);
+		// the existing bonds are kept
+		assert_eq!(Stablecoin::get_bond(0).account, 1);
+		assert_eq!(Stablecoin::get_bond(2).account, 3);
+		// the rejected bid stays in the queue
+		assert_eq!(Stablecoin::bond_bids().iter().map(|b| b.account).collect::<Vec<_>>(), vec![4]);
+
+		// a full queue does not fill any bids
+		let supply = Stablecoin::coin_supply();
+		assert_eq!(Stablecoin::contract_supply(supply, BaseUnit::get()), Ok(0));
+		assert_eq!(Stablecoin::bond_count(), 3);
+		MAX_BONDS.with(|v| *v.borrow_mut() = BondIndex::max_value() as u32);
+	});
+}
+
+#[test]
+fn max_bonds_is_clamped_to_the_queue_capacity() {
+	new_test_ext().execute_with(|| {
+		// beyond the capacity the queue would write over the oldest bonds
+		MAX_BONDS.with(|v| *v.borrow_mut() = u32::max_value());
+		assert_eq!(Stablecoin::max_bonds(), BondIndex::max_value() as usize);
+		MAX_BONDS.with(|v| *v.borrow_mut() = 3);
+		assert_eq!(Stablecoin::max_bonds(), 3);
+		MAX_BONDS.with(|v| *v.borrow_mut() = BondIndex::max_value() as u32);
+	});
+}
+
+#[test]
+fn contract_supply_never_creates_zero_payout_bonds() {
+	new_test_ext().execute_with(|| {
+		// the highest possible price rounds the removed quantity down the most
+		let price = Perbill::from_parts(Perbill::ACCURACY - 1);
+		Stablecoin::add_bid(Stablecoin::new_bid(1, price, 10 * BaseUnit::get()));
+		// a tiny contraction against the high-priced bid
+		assert_ok!(Stablecoin::contract_supply(Stablecoin::coin_supply(), 1));
+		let bonds: Vec<BondT> = Stablecoin::bonds().collect();
+		assert_eq!(bonds.len(), 1);
+		assert_ge!(bonds[0].payout, 1);
+
+		// a zero payout does not create a bond at all
+		let bid = Stablecoin::new_bid(2, price, 0);
+		assert!(Stablecoin::new_bonds_for_bid(&bid, 0, 0).is_empty());
+	});
+}
+
+#[test]
+fn contract_supply_turns_bid_dust_into_bond() {
+	new_test_ext().execute_with(|| {
+		// 20% of 5001 is 1000.2 --> payment of 1000
+		let price = Perbill::from_percent(20);
+		let bid = Stablecoin::new_bid(1, price, 5 * BaseUnit::get() + 1);
+		assert_eq!(bid.payment(), BaseUnit::get());
+		Stablecoin::add_bid(bid);
+
+		// filling the payment leaves a quantity of 1 which is worth 0 Coins
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::contract_supply(prev_supply, BaseUnit::get()));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply - BaseUnit::get());
+		assert_eq!(Stablecoin::bid_count(), 0);
+		let payouts: Vec<Coins> = Stablecoin::bonds().map(|b| b.payout).collect();
+		assert_eq!(payouts, vec![5 * BaseUnit::get() + 1]);
+	});
+}
+
+#[test]
+fn uncovered_contraction_is_carried_over() {
+	new_test_ext().execute_with(|| {
+		CONTRACTION_CARRYOVER.with(|v| *v.borrow_mut() = true);
+		let price = TEST_BASE_UNIT + TEST_BASE_UNIT / 4;
+		let bid = Stablecoin::new_bid(1, Perbill::from_percent(80), 2 * BaseUnit::get());
+		let payment = bid.payment();
+		Stablecoin::add_bid(bid);
+
+		let supply = Stablecoin::coin_supply();
+		let contract_by = Stablecoin::calculate_supply_change(price, TEST_BASE_UNIT, supply);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply - payment);
+		assert_eq!(Stablecoin::pending_contraction(), contract_by - payment);
+
+		// no bids left, so the whole contraction is carried over again
+		let pending = Stablecoin::pending_contraction();
+		let supply = Stablecoin::coin_supply();
+		let contract_by = Stablecoin::calculate_supply_change(price, TEST_BASE_UNIT, supply);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply);
+		assert_eq!(Stablecoin::pending_contraction(), contract_by + pending);
+
+		// the carryover is discarded once the price is on target
+		assert_ok!(Stablecoin::expand_or_contract_on_price(TEST_BASE_UNIT));
+		assert_eq!(Stablecoin::pending_contraction(), 0);
+	});
+}
+
+#[test]
+fn carried_over_contraction_is_capped_without_bids() {
+	new_test_ext().execute_with(|| {
+		CONTRACTION_CARRYOVER.with(|v| *v.borrow_mut() = true);
+		let price = TEST_BASE_UNIT + TEST_BASE_UNIT / 4;
+		let supply = Stablecoin::coin_supply();
+		let contractable = supply - MinimumSupply::get();
+		// the bid book is empty, so nothing is contracted but the carryover must stay bounded
+		for _ in 0..100 {
+			assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+			assert_eq!(Stablecoin::coin_supply(), supply);
+			assert_le!(Stablecoin::pending_contraction(), contractable);
+		}
+		assert_eq!(Stablecoin::pending_contraction(), contractable);
+
+		// once there are bids again the contraction does not underflow
+		let bid = Stablecoin::new_bid(1, Perbill::from_percent(80), 2 * BaseUnit::get());
+		let payment = bid.payment();
+		Stablecoin::add_bid(bid);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply - payment);
+		assert_eq!(Stablecoin::pending_contraction(), contractable - payment);
+		CONTRACTION_CARRYOVER.with(|v| *v.borrow_mut() = false);
+	});
+}
+
+#[test]
+fn uncovered_contraction_is_discarded() {
+	new_test_ext().execute_with(|| {
+		CONTRACTION_CARRYOVER.with(|v| *v.borrow_mut() = false);
+		let price = TEST_BASE_UNIT + TEST_BASE_UNIT / 4;
+		let bid = Stablecoin::new_bid(1, Perbill::from_percent(80), 2 * BaseUnit::get());
+		let payment = bid.payment();
+		Stablecoin::add_bid(bid);
+
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply - payment);
+		assert_eq!(Stablecoin::pending_contraction(), 0);
+	});
+}
+
+#[test]
+fn compute_supply_delta_test() {
+	let supply = 100 * TEST_BASE_UNIT;
+	let peg = (TEST_BASE_UNIT, TEST_BASE_UNIT);
+	let delta = |price| Stablecoin::compute_supply_delta(price, supply, peg);
+	assert_eq!(delta(TEST_BASE_UNIT), SupplyAction::Expand(0));
+	assert_eq!(delta(TEST_BASE_UNIT / 2), SupplyAction::Expand(supply));
+	assert_eq!(delta(TEST_BASE_UNIT * 4 / 5), SupplyAction::Expand(supply / 4));
+	assert_eq!(delta(TEST_BASE_UNIT * 5 / 4), SupplyAction::Contract(supply / 4));
+	assert_eq!(delta(TEST_BASE_UNIT * 2), SupplyAction::Contract(supply));
+	// extreme prices saturate
+	assert_eq!(delta(1), SupplyAction::Expand((TEST_BASE_UNIT - 1) * supply));
+	assert_eq!(delta(Coins::max_value()), SupplyAction::Contract(Coins::max_value()));
+
+	// the change is measured from the nearer end of the band
+	let band = (TEST_BASE_UNIT * 4 / 5, TEST_BASE_UNIT * 5 / 4);
+	let delta = |price| Stablecoin::compute_supply_delta(price, supply, band);
+	assert_eq!(delta(TEST_BASE_UNIT * 4 / 5), SupplyAction::Expand(0));
+	assert_eq!(delta(TEST_BASE_UNIT), SupplyAction::Expand(0));
+	assert_eq!(delta(TEST_BASE_UNIT * 5 / 4), SupplyAction::Expand(0));
+	assert_eq!(delta(TEST_BASE_UNIT * 2 / 5), SupplyAction::Expand(supply));
+	assert_eq!(delta(TEST_BASE_UNIT * 5 / 2), SupplyAction::Contract(supply));
+}
+
+#[test]
+fn coins_to_peg_matches_rebase() {
+	new_test_ext().execute_with(|| {
+		// The mock oracle returns random prices (shared between tests), so the supply change is
+		// checked for fixed prices with the helper used by `coins_to_peg`.
+		assert_eq!(Stablecoin::supply_change_on_price(TEST_BASE_UNIT), SupplyAction::Expand(0));
+
+		let price = TEST_BASE_UNIT - TEST_BASE_UNIT / 5;
+		let supply = Stablecoin::coin_supply();
+		let change = Stablecoin::supply_change_on_price(price);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(change, SupplyAction::Expand(Stablecoin::coin_supply() - supply));
+
+		let price = TEST_BASE_UNIT + TEST_BASE_UNIT / 10;
+		for _ in 0..MaximumBids::get() {
+			Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), 5 * BaseUnit::get()));
+		}
+		let supply = Stablecoin::coin_supply();
+		let change = Stablecoin::supply_change_on_price(price);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(change, SupplyAction::Contract(supply - Stablecoin::coin_supply()));
+		// the query does not change the supply
+		let supply = Stablecoin::coin_supply();
+		let _ = Stablecoin::coins_to_peg();
+		assert_eq!(Stablecoin::coin_supply(), supply);
+	});
+}
+
+#[test]
+fn contract_supply_bonds_cover_burned_coins() {
 	new_test_ext().execute_with(|| {
-		let bond_amount = Ratio::new(125, 100)
-			.checked_mul(&BaseUnit::get().into())
-			.map(|r| r.to_integer())
-			.expect("bond_amount should not have overflowed");
-		Stablecoin::add_bid(Bid::new(1, Perbill::from_percent(80), bond_amount));
-		Stablecoin::add_bid(Bid::new(2, Perbill::from_percent(75), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(90), 3 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(70), 3 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(33), 10 * BaseUnit::get()));
 
 		let prev_supply = Stablecoin::coin_supply();
-		let amount = 2 * BaseUnit::get();
-		assert_ok!(Stablecoin::contract_supply(prev_supply, amount));
+		let amount = 5 * BaseUnit::get();
+		let burned = Stablecoin::contract_supply(prev_supply, amount).expect("contraction should work");
+		assert_eq!(burned, amount);
+
+		let payouts: Vec<Coins> = Stablecoin::bonds().map(|b| b.payout).collect();
+		assert_eq!(payouts.len(), 3);
+		// 90% and 70% bids are filled completely
+		assert_eq!(payouts[0], 3 * BaseUnit::get());
+		assert_eq!(payouts[1], 3 * BaseUnit::get());
+		// the remaining 0.2 BaseUnit are bought at 33%
+		let remaining = amount
+			- Perbill::from_percent(90) * (3 * BaseUnit::get())
+			- Perbill::from_percent(70) * (3 * BaseUnit::get());
+		assert_eq!(payouts[2], remaining * 100 / 33);
+		assert_gt!(payouts.iter().sum::<Coins>(), burned);
+	});
+}
 
-		let bids = Stablecoin::bond_bids();
-		assert_eq!(bids.len(), 1, "exactly one bid should have been removed");
-		let remainging_bid_quantity = Fixed64::from_rational(667, 1_000)
-			.saturated_multiply_accumulate(BaseUnit::get())
-			- BaseUnit::get();
-		assert_eq!(
-			bids[0],
-			Bid::new(2, Perbill::from_percent(75), remainging_bid_quantity)
-		);
+#[test]
+fn contraction_bonds_cover_burned_coins_quickcheck() {
+	fn property(bids: Vec<(AccountId, u8, Coins)>, amount: Coins) -> TestResult {
+		new_test_ext().execute_with(|| {
+			// scale up to get amounts in the range of `BaseUnit`
+			let amount = 100 * amount;
+			let supply = Stablecoin::coin_supply();
+			if amount > supply - MinimumSupply::get() {
+				return TestResult::discard();
+			}
+			for (account, percent, quantity) in bids {
+				let price = Perbill::from_percent(percent as u32 % 100 + 1);
+				if price <= MinimumBondPrice::get() || quantity == 0 {
+					continue;
+				}
+				Stablecoin::add_bid(Stablecoin::new_bid(account, price, 100 * quantity));
+			}
 
-		let (start, _) = Stablecoin::bonds_range();
-		assert_eq!(Stablecoin::get_bond(start).payout, bond_amount);
-		assert_eq!(
-			Stablecoin::get_bond(start + 1).payout,
-			Fixed64::from_rational(333, 1_000).saturated_multiply_accumulate(BaseUnit::get())
-		);
+			let (_, prev_bonds_len) = Stablecoin::bonds_range();
+			let burned = Stablecoin::contract_supply(supply, amount).expect("contraction should not fail");
+			let new_bonds: Vec<_> = Stablecoin::bonds().skip(prev_bonds_len as usize).collect();
+			let total_payout: Coins = new_bonds.iter().map(|b| b.payout).sum();
+
+			assert_le!(burned, amount);
+			assert_eq!(Stablecoin::coin_supply(), supply - burned);
+			// bonds are bought at a discount so they need to pay out at least the burned coins
+			assert_ge!(total_payout, burned);
+			// but no more than the burned coins at the minimum bond price (modulo rounding)
+			assert_le!(
+				MinimumBondPrice::get() * total_payout,
+				burned + new_bonds.len() as u64
+			);
 
-		assert_eq!(
-			Stablecoin::coin_supply(),
-			prev_supply - amount,
-			"supply should be decreased by amount"
-		);
-	})
+			TestResult::passed()
+		})
+	}
+
+	seeded_quickcheck()
+		.min_tests_passed(5)
+		.tests(50)
+		.max_tests(500)
+		.quickcheck(property as fn(Vec<(u64, u8, u64)>, u64) -> TestResult)
 }
 
 #[test]
@@ -648,13 +3165,14 @@ fn expand_or_contract_quickcheck() {
 							message: Some("CoinSupplyOverflow")
 						})
 				));
+				assert!(Stablecoin::check_supply_invariant());
 			}
 
 			TestResult::passed()
 		})
 	}
 
-	QuickCheck::new()
+	seeded_quickcheck()
 		.min_tests_passed(5)
 		.tests(50)
 		.max_tests(500)
@@ -664,7 +3182,7 @@ fn expand_or_contract_quickcheck() {
 #[test]
 fn expand_or_contract_smoketest() {
 	new_test_ext().execute_with(|| {
-		let mut rng = rand::thread_rng();
+		let mut rng = seeded_rng();
 
 		let bonds: Vec<(u64, u64)> = (0..100)
 			.map(|_| (rng.gen_range(1, 200), rng.gen_range(1, 10 * BaseUnit::get())))
@@ -675,6 +3193,7 @@ fn expand_or_contract_smoketest() {
 		}
 
 		for _ in 0..150 {
+			RandomPrice::record_price();
 			let price = RandomPrice::fetch_price();
 			Stablecoin::on_block_with_price(0, price).unwrap_or_else(|e| {
 				log::error!("could not adjust supply: {:?}", e);
@@ -683,6 +3202,370 @@ fn expand_or_contract_smoketest() {
 	})
 }
 
+#[test]
+fn cumulative_seigniorage_tracks_shareholder_handouts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(Stablecoin::cumulative_seigniorage(), 0);
+		// the first expansion is partially payed out to bond holders
+		add_bond(Stablecoin::new_bond(11, BaseUnit::get()));
+		let amounts = [3 * BaseUnit::get(), 7, 42 * BaseUnit::get()];
+		for amount in amounts.iter() {
+			assert_ok!(Stablecoin::expand_supply(Stablecoin::coin_supply(), *amount));
+		}
+
+		let distributed: Vec<Coins> = stablecoin_events()
+			.into_iter()
+			.filter_map(|e| match e {
+				RawEvent::SeigniorageDistributed(amount) => Some(amount),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(distributed, vec![2 * BaseUnit::get(), 7, 42 * BaseUnit::get()]);
+		assert_eq!(Stablecoin::cumulative_seigniorage(), distributed.iter().sum::<Coins>());
+	});
+}
+
+#[test]
+fn seigniorage_to_shareholders_can_be_disabled() {
+	new_test_ext().execute_with(|| {
+		let amount = 10 * BaseUnit::get();
+		let supply = Stablecoin::coin_supply();
+		let balance = Stablecoin::get_balance(1);
+		assert_ok!(Stablecoin::expand_supply(supply, amount));
+		assert_eq!(Stablecoin::coin_supply(), supply + amount);
+		assert_eq!(Stablecoin::get_balance(1), balance + amount / 10);
+
+		SEIGNIORAGE_TO_SHAREHOLDERS.with(|v| *v.borrow_mut() = false);
+		let supply = Stablecoin::coin_supply();
+		let balance = Stablecoin::get_balance(1);
+		// without bonds nothing is created
+		assert_ok!(Stablecoin::expand_supply(supply, amount));
+		assert_eq!(Stablecoin::coin_supply(), supply);
+		assert_eq!(Stablecoin::get_balance(1), balance);
+
+		// bonds are still payed out
+		add_bond(Stablecoin::new_bond(11, BaseUnit::get()));
+		assert_ok!(Stablecoin::expand_supply(supply, amount));
+		assert_eq!(Stablecoin::coin_supply(), supply + BaseUnit::get());
+		assert_eq!(Stablecoin::get_balance(11), BaseUnit::get());
+		assert_eq!(Stablecoin::get_balance(1), balance);
+		SEIGNIORAGE_TO_SHAREHOLDERS.with(|v| *v.borrow_mut() = true);
+	});
+}
+
+#[test]
+fn on_initialize_weight_grows_with_queue_size() {
+	new_test_ext().execute_with(|| {
+		let adjustment_block = AdjustmentFrequency::get();
+		let empty = Stablecoin::on_initialize_weight(adjustment_block);
+
+		add_bond(Stablecoin::new_bond(3, BaseUnit::get()));
+		let with_bond = Stablecoin::on_initialize_weight(adjustment_block);
+		assert_gt!(with_bond, empty);
+
+		Stablecoin::add_bid(Stablecoin::new_bid(4, Perbill::from_percent(80), BaseUnit::get()));
+		let with_bid = Stablecoin::on_initialize_weight(adjustment_block);
+		assert_gt!(with_bid, with_bond);
+		assert_eq!(with_bid, empty + 2 * QUEUE_ITEM_WEIGHT);
+
+		// blocks without an adjustment do not touch the queues
+		assert_eq!(Stablecoin::on_initialize_weight(adjustment_block + 1), PRICE_CHECK_WEIGHT);
+	});
+}
+
+#[test]
+fn on_initialize_adjusts_only_once_per_block() {
+	new_test_ext().execute_with(|| {
+		let block = AdjustmentFrequency::get();
+		assert_eq!(Stablecoin::last_rebase_block(), None);
+		<Stablecoin as OnInitialize<BlockNumber>>::on_initialize(block);
+		assert_eq!(Stablecoin::last_rebase_block(), Some(block));
+		let supply = Stablecoin::coin_supply();
+		let last_rebase = Stablecoin::last_rebase();
+
+		// running again for the same block does not rebase again
+		assert_eq!(<Stablecoin as OnInitialize<BlockNumber>>::on_initialize(block), 0);
+		assert_eq!(Stablecoin::coin_supply(), supply);
+		assert_eq!(Stablecoin::last_rebase(), last_rebase);
+
+		// blocks without an adjustment are not recorded
+		<Stablecoin as OnInitialize<BlockNumber>>::on_initialize(block + 1);
+		assert_eq!(Stablecoin::last_rebase_block(), Some(block));
+	});
+}
+
+#[test]
+fn poke_rebases_once_per_block() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(TEST_BASE_UNIT / 2));
+		let supply = Stablecoin::coin_supply();
+
+		// not an adjustment block, but anyone can trigger the rebase
+		assert_ok!(Stablecoin::poke(Origin::signed(5)));
+		let expanded = Stablecoin::coin_supply() - supply;
+		assert_gt!(expanded, 0);
+		assert_eq!(Stablecoin::last_rebase_block(), Some(3));
+		assert_eq!(Stablecoin::last_rebase(), Some((3, SupplyAction::Expand(expanded))));
+		assert!(stablecoin_events().contains(&RawEvent::ExpandedSupply(expanded)));
+
+		// a second poke in the same block does nothing
+		assert_noop!(Stablecoin::poke(Origin::signed(5)), Error::<Test>::AlreadyRebased);
+
+		// the next block can be poked again
+		System::set_block_number(4);
+		assert_ok!(Stablecoin::poke(Origin::signed(6)));
+		assert_gt!(Stablecoin::coin_supply(), supply + expanded);
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = None);
+	});
+}
+
+#[test]
+fn failed_poke_does_not_block_the_rebase() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(0));
+		assert_noop!(Stablecoin::poke(Origin::signed(5)), Error::<Test>::ZeroPrice);
+		assert_eq!(Stablecoin::last_rebase_block(), None);
+
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(TEST_BASE_UNIT / 2));
+		assert_ok!(Stablecoin::poke(Origin::signed(5)));
+		assert_eq!(Stablecoin::last_rebase_block(), Some(3));
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = None);
+	});
+}
+
+#[test]
+fn last_rebase_tracks_actual_supply_changes() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::last_rebase(), None);
+
+		// price too low --> expand
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::on_block_with_price(2, TEST_BASE_UNIT / 2));
+		let expanded = Stablecoin::coin_supply() - supply;
+		assert!(expanded > 0);
+		assert_eq!(Stablecoin::last_rebase(), Some((2, SupplyAction::Expand(expanded))));
+
+		// not an adjustment block
+		assert_ok!(Stablecoin::on_block_with_price(3, TEST_BASE_UNIT / 2));
+		// price on target
+		assert_ok!(Stablecoin::on_block_with_price(4, TEST_BASE_UNIT));
+		// price too high but no bids to contract with
+		assert_ok!(Stablecoin::on_block_with_price(6, 2 * TEST_BASE_UNIT));
+		assert_eq!(Stablecoin::last_rebase(), Some((2, SupplyAction::Expand(expanded))));
+
+		// price too high with a bid --> contract
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_percent(80),
+			10 * BaseUnit::get()
+		));
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::on_block_with_price(8, TEST_BASE_UNIT + TEST_BASE_UNIT / 100));
+		let contracted = supply - Stablecoin::coin_supply();
+		assert!(contracted > 0);
+		assert_eq!(Stablecoin::last_rebase(), Some((8, SupplyAction::Contract(contracted))));
+	});
+}
+
+#[test]
+fn out_of_bounds_prices_skip_rebase() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		ACCEPTABLE_PRICE.with(|v| *v.borrow_mut() = (TEST_BASE_UNIT / 10, 10 * TEST_BASE_UNIT));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), 10 * BaseUnit::get()));
+		let supply = Stablecoin::coin_supply();
+
+		let too_low = TEST_BASE_UNIT / 10 - 1;
+		assert_ok!(Stablecoin::on_block_with_price(2, too_low));
+		let too_high = 10 * TEST_BASE_UNIT + 1;
+		assert_ok!(Stablecoin::on_block_with_price(4, too_high));
+		assert_eq!(Stablecoin::coin_supply(), supply);
+		assert_eq!(Stablecoin::last_rebase(), None);
+		let events = stablecoin_events();
+		assert!(events.contains(&RawEvent::PriceOutOfBounds(too_low)));
+		assert!(events.contains(&RawEvent::PriceOutOfBounds(too_high)));
+
+		// readings at the bounds are accepted
+		assert_ok!(Stablecoin::on_block_with_price(6, TEST_BASE_UNIT / 10));
+		assert_gt!(Stablecoin::coin_supply(), supply);
+		ACCEPTABLE_PRICE.with(|v| *v.borrow_mut() = (0, Coins::max_value()));
+	});
+}
+
+#[test]
+fn coins_to_peg_ignores_out_of_bounds_prices() {
+	new_test_ext().execute_with(|| {
+		ACCEPTABLE_PRICE.with(|v| *v.borrow_mut() = (TEST_BASE_UNIT / 10, 10 * TEST_BASE_UNIT));
+		// no rebase happens at these readings, so there is no change to report
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(TEST_BASE_UNIT / 10 - 1));
+		assert_eq!(Stablecoin::coins_to_peg(), SupplyAction::Expand(0));
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(10 * TEST_BASE_UNIT + 1));
+		assert_eq!(Stablecoin::coins_to_peg(), SupplyAction::Expand(0));
+
+		// readings within the bounds are reported
+		let price = TEST_BASE_UNIT / 2;
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = Some(price));
+		assert_eq!(Stablecoin::coins_to_peg(), Stablecoin::supply_change_on_price(price));
+		assert_ne!(Stablecoin::coins_to_peg(), SupplyAction::Expand(0));
+		PRICE_OVERRIDE.with(|v| *v.borrow_mut() = None);
+		ACCEPTABLE_PRICE.with(|v| *v.borrow_mut() = (0, Coins::max_value()));
+	});
+}
+
+#[test]
+fn rebases_in_range_returns_the_window() {
+	new_test_ext().execute_with(|| {
+		let prices = [TEST_BASE_UNIT / 2, TEST_BASE_UNIT, TEST_BASE_UNIT / 4, TEST_BASE_UNIT / 2];
+		let mut expected = Vec::new();
+		for (i, price) in prices.iter().enumerate() {
+			let block = 2 * (i as BlockNumber + 1);
+			let supply = Stablecoin::coin_supply();
+			assert_ok!(Stablecoin::on_block_with_price(block, *price));
+			if Stablecoin::coin_supply() != supply {
+				expected.push((block, SupplyAction::Expand(Stablecoin::coin_supply() - supply)));
+			}
+		}
+		// the price was on target in block 4
+		assert_eq!(expected.len(), 3);
+
+		assert_eq!(Stablecoin::rebases_in_range(0, 100), expected);
+		assert_eq!(Stablecoin::rebases_in_range(3, 6), vec![expected[1].clone()]);
+		assert_eq!(Stablecoin::rebases_in_range(2, 2), vec![expected[0].clone()]);
+		assert_eq!(Stablecoin::rebases_in_range(9, 100), vec![]);
+	});
+}
+
+#[test]
+fn extreme_prices_do_not_invert_the_rebase() {
+	new_test_ext().execute_with(|| {
+		let supply = Stablecoin::coin_supply();
+		let extreme_price = i64::max_value() as Coins + 1;
+		assert_eq!(
+			Stablecoin::calculate_supply_change(extreme_price, TEST_BASE_UNIT, supply),
+			Coins::max_value()
+		);
+		assert_eq!(
+			Stablecoin::calculate_supply_change(Coins::max_value(), 1, supply),
+			Coins::max_value()
+		);
+
+		// contracting by more than the supply is rejected instead of expanding
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), BaseUnit::get()));
+		assert_noop!(
+			Stablecoin::expand_or_contract_on_price(extreme_price),
+			Error::<Test>::CoinSupplyUnderflow
+		);
+		assert_noop!(
+			Stablecoin::expand_or_contract_on_price(Coins::max_value()),
+			Error::<Test>::CoinSupplyUnderflow
+		);
+		assert_eq!(Stablecoin::coin_supply(), supply);
+	});
+}
+
+#[test]
+fn target_band_limits_rebases() {
+	new_test_ext().execute_with(|| {
+		let low = TEST_BASE_UNIT - TEST_BASE_UNIT / 100;
+		let high = TEST_BASE_UNIT + TEST_BASE_UNIT / 100;
+		assert_noop!(
+			Stablecoin::set_target_band(Origin::signed(1), Some((low, high))),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Stablecoin::set_target_band(Origin::ROOT, Some((high, low))),
+			Error::<Test>::InvalidTargetBand
+		);
+		assert_ok!(Stablecoin::set_target_band(Origin::ROOT, Some((low, high))));
+		assert_eq!(Stablecoin::target_band(), (low, high));
+
+		// inside the band
+		let supply = Stablecoin::coin_supply();
+		for price in [low, TEST_BASE_UNIT, high].iter() {
+			assert_ok!(Stablecoin::expand_or_contract_on_price(*price));
+			assert_eq!(Stablecoin::coin_supply(), supply);
+		}
+
+		// below the band the deviation is measured from `low`
+		let price = low - low / 10;
+		let expected = Stablecoin::calculate_supply_change(low, price, supply);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply + expected);
+
+		// above the band the deviation is measured from `high`
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), 100 * BaseUnit::get()));
+		let supply = Stablecoin::coin_supply();
+		let price = high + high / 10;
+		let expected = Stablecoin::calculate_supply_change(price, high, supply);
+		assert_ok!(Stablecoin::expand_or_contract_on_price(price));
+		assert_eq!(Stablecoin::coin_supply(), supply - expected);
+
+		// reset to the base unit
+		assert_ok!(Stablecoin::set_target_band(Origin::ROOT, None));
+		assert_eq!(Stablecoin::target_band(), (TEST_BASE_UNIT, TEST_BASE_UNIT));
+	});
+}
+
+#[test]
+fn small_deviations_within_the_target_band_do_not_rebase() {
+	new_test_ext().execute_with(|| {
+		let low = TEST_BASE_UNIT - TEST_BASE_UNIT / 20;
+		let high = TEST_BASE_UNIT + TEST_BASE_UNIT / 20;
+		assert_ok!(Stablecoin::set_target_band(Origin::ROOT, Some((low, high))));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), 100 * BaseUnit::get()));
+
+		// wiggle around the peg for many adjustment periods
+		let supply = Stablecoin::coin_supply();
+		let deviations = [0, 10, -10, 25, -25, 49, -49, 50, -50];
+		let mut block = 0;
+		for deviation in deviations.iter().cycle().take(100) {
+			block += AdjustmentFrequency::get();
+			let price = (TEST_BASE_UNIT as i64 + deviation) as Coins;
+			assert_ok!(Stablecoin::on_block_with_price(block, price));
+			assert_eq!(Stablecoin::coin_supply(), supply, "price {} should not rebase", price);
+		}
+		assert_eq!(Stablecoin::last_rebase(), None);
+
+		// just outside of the band
+		block += AdjustmentFrequency::get();
+		assert_ok!(Stablecoin::on_block_with_price(block, low - 1));
+		assert_gt!(Stablecoin::coin_supply(), supply);
+		let supply = Stablecoin::coin_supply();
+		block += AdjustmentFrequency::get();
+		assert_ok!(Stablecoin::on_block_with_price(block, high + 1));
+		assert_lt!(Stablecoin::coin_supply(), supply);
+	});
+}
+
+#[test]
+fn repeg_changes_the_target() {
+	new_test_ext().execute_with(|| {
+		let prev_supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_or_contract_on_price(TEST_BASE_UNIT));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply, "price is on target");
+
+		let new_base_unit = TEST_BASE_UNIT + TEST_BASE_UNIT / 4;
+		assert_noop!(
+			Stablecoin::repeg(Origin::signed(1), new_base_unit),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(Stablecoin::repeg(Origin::ROOT, 0), Error::<Test>::ZeroBaseUnit);
+		assert_ok!(Stablecoin::repeg(Origin::ROOT, new_base_unit));
+		assert_eq!(Stablecoin::base_unit(), new_base_unit);
+
+		// the same price is now too low compared to the new base unit
+		assert_ok!(Stablecoin::expand_or_contract_on_price(TEST_BASE_UNIT));
+		assert_eq!(Stablecoin::coin_supply(), prev_supply + prev_supply / 4);
+
+		let supply = Stablecoin::coin_supply();
+		assert_ok!(Stablecoin::expand_or_contract_on_price(new_base_unit));
+		assert_eq!(Stablecoin::coin_supply(), supply, "price is on the new target");
+	});
+}
+
 #[test]
 fn supply_change_calculation() {
 	let price = TEST_BASE_UNIT + 100;
@@ -692,3 +3575,320 @@ fn supply_change_calculation() {
 	assert_ge!(contract_by, u64::max_value() / 10 - 1);
 	assert_le!(contract_by, u64::max_value() / 10 + 1);
 }
+
+// ------------------------------------------------------------
+// queries
+#[test]
+fn core_queries() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::bid_count(), 0);
+		assert_eq!(Stablecoin::bond_count(), 0);
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(70), BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(3, BaseUnit::get()));
+		assert_eq!(Stablecoin::bid_count(), 2);
+		assert_eq!(Stablecoin::bond_count(), 1);
+
+		let (price, base_unit) = Stablecoin::current_peg_ratio();
+		assert_gt!(price, 0);
+		assert_eq!(base_unit, TEST_BASE_UNIT);
+	});
+}
+
+#[test]
+fn all_balances_test() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		assert_ok!(Stablecoin::transfer_from_to(&1, &3, 3 * BaseUnit::get()));
+		assert_ok!(Stablecoin::transfer_from_to(&2, &7, 7 * BaseUnit::get()));
+
+		let mut balances = Stablecoin::all_balances();
+		balances.sort();
+		let half = InitialSupply::get() / 2;
+		assert_eq!(
+			balances,
+			vec![
+				(1, half - 3 * BaseUnit::get()),
+				(2, half - 7 * BaseUnit::get()),
+				(3, 3 * BaseUnit::get()),
+				(7, 7 * BaseUnit::get()),
+			]
+		);
+	});
+}
+
+#[test]
+fn bond_stats_test() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::bond_stats(), (0, 0, 0, 0));
+		for payout in &[3, 7, 2, 5] {
+			add_bond(Stablecoin::new_bond(1, payout * BaseUnit::get()));
+		}
+		assert_eq!(
+			Stablecoin::bond_stats(),
+			(2 * BaseUnit::get(), 7 * BaseUnit::get(), 17 * BaseUnit::get(), 4)
+		);
+	});
+}
+
+#[test]
+fn account_total_value_test() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let acc = 1;
+		let price = Perbill::from_percent(80);
+		let quantity = 5 * BaseUnit::get();
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(acc), price, quantity));
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(2), price, quantity));
+		add_bond(Stablecoin::new_bond(acc, 3 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(2, 7 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(acc, 2 * BaseUnit::get()));
+
+		let balance = InitialSupply::get() / 2 - price * quantity;
+		assert_eq!(Stablecoin::get_balance(acc), balance);
+		assert_eq!(
+			Stablecoin::account_total_value(&acc),
+			balance + 5 * BaseUnit::get() + price * quantity
+		);
+
+		// expired bonds are not worth anything
+		System::set_block_number(System::block_number() + ExpirationPeriod::get());
+		assert_eq!(Stablecoin::account_total_value(&acc), balance + price * quantity);
+	});
+}
+
+#[test]
+fn simulate_contraction_matches_contract_supply() {
+	new_test_ext().execute_with(|| {
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(75), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(50), 4 * BaseUnit::get()));
+		let bids = Stablecoin::bond_bids();
+
+		let amount = 3 * BaseUnit::get();
+		let fills = Stablecoin::simulate_contraction(amount);
+		assert_eq!(Stablecoin::bond_bids(), bids, "simulation should not change the bids");
+		let accounts: Vec<AccountId> = fills.iter().map(|(acc, _, _)| *acc).collect();
+		assert_eq!(accounts, vec![1, 2]);
+
+		let prev_supply = Stablecoin::coin_supply();
+		let burned = Stablecoin::contract_supply(prev_supply, amount).unwrap();
+		assert_eq!(burned, fills.iter().map(|(_, paid, _)| paid).sum::<Coins>());
+		let bonds: Vec<(AccountId, Coins)> = Stablecoin::bonds().map(|b| (b.account, b.payout)).collect();
+		let expected: Vec<(AccountId, Coins)> = fills
+			.into_iter()
+			.map(|(acc, _, payout)| (acc, payout))
+			.collect();
+		assert_eq!(bonds, expected);
+	});
+}
+
+#[test]
+fn simulation_queries_follow_contract_supply_limits() {
+	new_test_ext().execute_with(|| {
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(30), 1001));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(20), 1000));
+		// the rest of the first bid is worth less than a Coin and is added to its bond
+		assert_eq!(Stablecoin::simulate_contraction(300), vec![(1, 300, 1001)]);
+		assert_eq!(Stablecoin::marginal_bid(300), Some((Perbill::from_percent(30), 1001)));
+
+		// the bonds of the second bid do not fit into the bond queue
+		MAX_BONDS.with(|v| *v.borrow_mut() = 3);
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = 500);
+		let amount = 500;
+		assert_eq!(Stablecoin::simulate_contraction(amount), vec![(1, 300, 1001)]);
+		assert_eq!(Stablecoin::marginal_bid(amount), None);
+
+		assert_eq!(Stablecoin::contract_supply(Stablecoin::coin_supply(), amount), Ok(300));
+		let payouts: Vec<Coins> = Stablecoin::bonds().map(|b| b.payout).collect();
+		assert_eq!(payouts, vec![500, 500, 1]);
+		MAX_BONDS.with(|v| *v.borrow_mut() = BondIndex::max_value() as u32);
+		MAXIMUM_BOND_SIZE.with(|v| *v.borrow_mut() = Coins::max_value());
+	});
+}
+
+#[test]
+fn best_bid_price_and_total_bid_demand() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::best_bid_price(), None);
+		assert_eq!(Stablecoin::total_bid_demand(), 0);
+
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(90), 3 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(70), 5 * BaseUnit::get()));
+
+		assert_eq!(Stablecoin::best_bid_price(), Some(Perbill::from_percent(90)));
+		// 1000 + 2700 + 3500
+		assert_eq!(Stablecoin::total_bid_demand(), 7200);
+	});
+}
+
+#[test]
+fn fill_fraction_depends_on_queue_position() {
+	new_test_ext().execute_with(|| {
+		let expected_contraction = 3 * BaseUnit::get();
+		let fraction = |percent| {
+			Stablecoin::fill_fraction(Perbill::from_percent(percent), BaseUnit::get(), expected_contraction)
+		};
+		// empty book --> any bid fills completely
+		assert_eq!(fraction(60), Perbill::one());
+
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(90), BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(80), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(70), BaseUnit::get()));
+
+		// front: filled before all other bids
+		assert_eq!(fraction(95), Perbill::one());
+		// middle: 900 + 1600 are filled first (including the bid with the same price),
+		// leaving 500 of the 800 Coins
+		assert_eq!(fraction(80), Perbill::from_rational_approximation(500u64, 800u64));
+		// tail: 900 + 1600 + 700 exceed the contraction
+		assert_eq!(fraction(60), Perbill::zero());
+
+		// nothing to fill
+		assert_eq!(
+			Stablecoin::fill_fraction(Perbill::from_percent(95), 0, expected_contraction),
+			Perbill::zero()
+		);
+	});
+}
+
+#[test]
+fn overview_matches_individual_queries() {
+	new_test_ext().execute_with(|| {
+		let overview = Stablecoin::overview();
+		assert_eq!(overview.coin_supply, InitialSupply::get());
+		assert_eq!(overview.share_supply, 10);
+		assert_eq!(overview.bid_count, 0);
+		assert_eq!(overview.bond_count, 0);
+		assert_eq!(overview.outstanding_bond_liability, 0);
+		assert_eq!(overview.last_rebase, None);
+
+		add_bond(Stablecoin::new_bond(3, 2 * BaseUnit::get()));
+		add_bond(Stablecoin::new_bond(4, 3 * BaseUnit::get()));
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_percent(80),
+			5 * BaseUnit::get()
+		));
+		assert_ok!(Stablecoin::on_block_with_price(2, TEST_BASE_UNIT / 2));
+
+		let overview = Stablecoin::overview();
+		assert_eq!(overview.coin_supply, Stablecoin::coin_supply());
+		assert_eq!(Ok(overview.share_supply), Stablecoin::checked_share_supply(&Stablecoin::shares()));
+		assert_eq!(overview.shareholder_count as usize, Stablecoin::shares().len());
+		assert_eq!(overview.bid_count, Stablecoin::bid_count());
+		assert_eq!(overview.bond_count, Stablecoin::bond_count());
+		assert_eq!(overview.outstanding_bond_liability, Stablecoin::bond_stats().2);
+		assert_eq!(overview.current_peg_ratio, Stablecoin::current_peg_ratio());
+		assert_eq!(overview.last_rebase, Stablecoin::last_rebase());
+		assert!(overview.last_rebase.is_some());
+	});
+}
+
+#[test]
+fn price_queries_do_not_change_state() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Stablecoin::on_initialize(1);
+		let root_before = sp_io::storage::root();
+		let ratio = Stablecoin::current_peg_ratio();
+		let to_peg = Stablecoin::coins_to_peg();
+		let overview = Stablecoin::overview();
+		assert_eq!(Stablecoin::current_peg_ratio(), ratio);
+		assert_eq!(Stablecoin::coins_to_peg(), to_peg);
+		assert_eq!(Stablecoin::overview(), overview);
+		assert_eq!(sp_io::storage::root(), root_before);
+	});
+}
+
+#[test]
+fn supply_invariant_test() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Stablecoin::circulating_supply(), InitialSupply::get());
+		assert!(Stablecoin::check_supply_invariant());
+
+		assert_ok!(Stablecoin::bid_for_bond_simple(
+			Origin::signed(1),
+			Perbill::from_percent(80),
+			5 * BaseUnit::get()
+		));
+		assert!(Stablecoin::check_supply_invariant());
+
+		// coins created without updating the supply break the invariant
+		Stablecoin::add_balance(&11, 1);
+		assert!(!Stablecoin::check_supply_invariant());
+	});
+}
+
+#[test]
+fn marginal_bid_test() {
+	new_test_ext().execute_with(|| {
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(90), 2 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(80), 5 * BaseUnit::get()));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(70), 10 * BaseUnit::get()));
+		let total = Stablecoin::bond_bids()
+			.iter()
+			.fold(0, |total: Coins, bid| total + bid.payment());
+
+		assert_eq!(Stablecoin::marginal_bid(0), None);
+		// the first bid is filled exactly
+		assert_eq!(
+			Stablecoin::marginal_bid(1800),
+			Some((Perbill::from_percent(90), 2 * BaseUnit::get()))
+		);
+		// 1800 from the first bid, 1200 of 4000 from the second bid
+		assert_eq!(Stablecoin::marginal_bid(3000), Some((Perbill::from_percent(80), 1500)));
+		assert_eq!(
+			Stablecoin::marginal_bid(total),
+			Some((Perbill::from_percent(70), 10 * BaseUnit::get()))
+		);
+		assert_eq!(Stablecoin::marginal_bid(total + 1), None);
+		// the query does not change the bids
+		assert_eq!(Stablecoin::bid_count(), 3);
+	});
+}
+
+#[test]
+fn my_bid_priorities_reports_queue_positions() {
+	new_test_ext().execute_with(|| {
+		let quantity = BaseUnit::get();
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(50), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(2, Perbill::from_percent(90), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(80), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(3, Perbill::from_percent(60), quantity));
+		Stablecoin::add_bid(Stablecoin::new_bid(1, Perbill::from_percent(90), quantity));
+
+		let priorities: Vec<(u32, Perbill)> = Stablecoin::my_bid_priorities(&1)
+			.into_iter()
+			.map(|(pos, bid)| (pos, bid.price))
+			.collect();
+		// the earlier bid of account 2 at 90% is filled first
+		assert_eq!(
+			priorities,
+			vec![
+				(1, Perbill::from_percent(90)),
+				(2, Perbill::from_percent(80)),
+				(4, Perbill::from_percent(50)),
+			]
+		);
+		let positions: Vec<u32> = Stablecoin::my_bid_priorities(&3).iter().map(|(pos, _)| *pos).collect();
+		assert_eq!(positions, vec![3]);
+		assert!(Stablecoin::my_bid_priorities(&4).is_empty());
+	});
+}
+
+#[test]
+fn can_bid_accounts_for_locked_coins() {
+	new_test_ext_with(vec![1, 2]).execute_with(|| {
+		let acc = 1;
+		let price = Perbill::from_percent(50);
+		let balance = Stablecoin::get_balance(acc);
+		assert!(Stablecoin::can_bid(&acc, price, 2 * balance));
+		assert!(!Stablecoin::can_bid(&acc, price, 2 * balance + 2));
+		assert!(!Stablecoin::can_bid(&acc, Perbill::from_percent(5), BaseUnit::get()));
+
+		// lock half of the balance in a bid
+		assert_ok!(Stablecoin::bid_for_bond_simple(Origin::signed(acc), price, balance));
+		assert!(Stablecoin::can_bid(&acc, price, balance));
+		assert!(!Stablecoin::can_bid(&acc, price, 2 * balance));
+	});
+}
@@ -34,7 +34,7 @@ pub use balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	StorageValue, construct_runtime, parameter_types,
-	traits::Randomness,
+	traits::{Get, Randomness},
 	weights::Weight,
 };
 
@@ -265,6 +265,7 @@ impl system::offchain::CreateTransaction<Runtime, UncheckedExtrinsic> for Runtim
 			system::CheckNonce::<Runtime>::from(index),
 			system::CheckWeight::<Runtime>::new(),
 			transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+			stablecoin::CheckBidAffordable::<Runtime>::new(),
 		);
 
 		let raw_payload = SignedPayload::new(call, extra).map_err(|e| {
@@ -293,6 +294,35 @@ parameter_types! {
 	pub const InitialSupply: Coins = 1000 * BaseUnit::get();
 	pub const MinimumSupply: Coins = BaseUnit::get();
 	pub const MinimumBondPrice: Perbill = Perbill::from_percent(10);
+	pub const ContractionCarryover: bool = false;
+	pub const MinSeigniorageBalance: Coins = 0;
+	pub const MaxLadderSize: u32 = 10;
+	pub const SeigniorageFallback: Option<AccountId> = None;
+	pub const MaxBidIntakePerBlock: Coins = 1_000_000 * BaseUnit::get();
+	pub const MaxShareOfExpansion: Perbill = Perbill::from_percent(100);
+	pub const ContractionReserve: Option<AccountId> = None;
+	pub const BidCooldown: BlockNumber = 0;
+	pub const BondPayoutMode: stablecoin::BondPayoutMode = stablecoin::BondPayoutMode::Fifo;
+	pub const MaxCoinSupply: Coins = Coins::max_value();
+	pub const MinAcceptablePrice: Coins = BaseUnit::get() / 10;
+	pub const MaxAcceptablePrice: Coins = 10 * BaseUnit::get();
+	pub const BondsExpire: bool = true;
+	pub const MaximumBondSize: Coins = 1_000_000 * BaseUnit::get();
+	pub const SeigniorageToShareholders: bool = true;
+	pub const MaxBonds: u32 = 10_000;
+	pub const ShareTransferCooldown: BlockNumber = 0;
+	pub const ExpiredBondTreasury: Option<AccountId> = None;
+	pub const MinimumTransfer: Coins = BaseUnit::get() / 1_000;
+	pub const KeepAlive: bool = true;
+	pub const MaxBatchSize: u32 = 10;
+}
+
+/// The account allowed to submit prices to the stablecoin, the sudo key.
+pub struct OracleAccount;
+impl Get<AccountId> for OracleAccount {
+	fn get() -> AccountId {
+		Sudo::key()
+	}
 }
 
 impl stablecoin::Trait for Runtime {
@@ -305,6 +335,28 @@ impl stablecoin::Trait for Runtime {
 	type InitialSupply = InitialSupply;
 	type MinimumSupply = MinimumSupply;
 	type MinimumBondPrice = MinimumBondPrice;
+	type ContractionCarryover = ContractionCarryover;
+	type MinSeigniorageBalance = MinSeigniorageBalance;
+	type MaxLadderSize = MaxLadderSize;
+	type SeigniorageFallback = SeigniorageFallback;
+	type MaxBidIntakePerBlock = MaxBidIntakePerBlock;
+	type MaxShareOfExpansion = MaxShareOfExpansion;
+	type ContractionReserve = ContractionReserve;
+	type BidCooldown = BidCooldown;
+	type BondPayoutMode = BondPayoutMode;
+	type MaxCoinSupply = MaxCoinSupply;
+	type MinAcceptablePrice = MinAcceptablePrice;
+	type MaxAcceptablePrice = MaxAcceptablePrice;
+	type BondsExpire = BondsExpire;
+	type MaximumBondSize = MaximumBondSize;
+	type OracleAccount = OracleAccount;
+	type SeigniorageToShareholders = SeigniorageToShareholders;
+	type MaxBonds = MaxBonds;
+	type ShareTransferCooldown = ShareTransferCooldown;
+	type ExpiredBondTreasury = ExpiredBondTreasury;
+	type MinimumTransfer = MinimumTransfer;
+	type KeepAlive = KeepAlive;
+	type MaxBatchSize = MaxBatchSize;
 
 	type CoinPrice = price::Module<Runtime>;
 }
@@ -347,7 +399,8 @@ pub type SignedExtra = (
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
-	transaction_payment::ChargeTransactionPayment<Runtime>
+	transaction_payment::ChargeTransactionPayment<Runtime>,
+	stablecoin::CheckBidAffordable<Runtime>
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -444,4 +497,89 @@ impl_runtime_apis! {
 			Grandpa::grandpa_authorities()
 		}
 	}
+
+	impl stablecoin::runtime_api::StablecoinApi<Block, AccountId, BlockNumber> for Runtime {
+		fn coin_supply() -> Coins {
+			Stablecoin::coin_supply()
+		}
+
+		fn get_balance(who: AccountId) -> Coins {
+			Stablecoin::get_balance(who)
+		}
+
+		fn bid_count() -> u32 {
+			Stablecoin::bid_count()
+		}
+
+		fn bond_count() -> stablecoin::BondIndex {
+			Stablecoin::bond_count()
+		}
+
+		fn bond_stats() -> (Coins, Coins, Coins, u32) {
+			Stablecoin::bond_stats()
+		}
+
+		fn current_peg_ratio() -> (Coins, Coins) {
+			Stablecoin::current_peg_ratio()
+		}
+
+		fn coins_to_peg() -> stablecoin::SupplyAction {
+			Stablecoin::coins_to_peg()
+		}
+
+		fn account_total_value(who: AccountId) -> Coins {
+			Stablecoin::account_total_value(&who)
+		}
+
+		fn all_balances() -> Vec<(AccountId, Coins)> {
+			Stablecoin::all_balances()
+		}
+
+		fn last_rebase() -> Option<(BlockNumber, stablecoin::SupplyAction)> {
+			Stablecoin::last_rebase()
+		}
+
+		fn rebases_in_range(
+			from: BlockNumber,
+			to: BlockNumber,
+		) -> Vec<(BlockNumber, stablecoin::SupplyAction)> {
+			Stablecoin::rebases_in_range(from, to)
+		}
+
+		fn can_bid(who: AccountId, price: Perbill, quantity: Coins) -> bool {
+			Stablecoin::can_bid(&who, price, quantity)
+		}
+
+		fn my_bid_priorities(who: AccountId) -> Vec<(u32, stablecoin::Bid<AccountId, BlockNumber>)> {
+			Stablecoin::my_bid_priorities(&who)
+		}
+
+		fn simulate_contraction(amount: Coins) -> Vec<(AccountId, Coins, Coins)> {
+			Stablecoin::simulate_contraction(amount)
+		}
+
+		fn marginal_bid(amount: Coins) -> Option<(Perbill, Coins)> {
+			Stablecoin::marginal_bid(amount)
+		}
+
+		fn cumulative_seigniorage() -> Coins {
+			Stablecoin::cumulative_seigniorage()
+		}
+
+		fn overview() -> stablecoin::StablecoinOverview<BlockNumber> {
+			Stablecoin::overview()
+		}
+
+		fn best_bid_price() -> Option<Perbill> {
+			Stablecoin::best_bid_price()
+		}
+
+		fn total_bid_demand() -> Coins {
+			Stablecoin::total_bid_demand()
+		}
+
+		fn fill_fraction(price: Perbill, quantity: Coins, expected_contraction: Coins) -> Perbill {
+			Stablecoin::fill_fraction(price, quantity, expected_contraction)
+		}
+	}
 }
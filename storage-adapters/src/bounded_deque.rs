@@ -35,6 +35,15 @@ use frame_support::storage::{StorageMap, StorageValue};
 use num_traits::{WrappingAdd, WrappingSub};
 
 type DefaultIdx = u16;
+
+/// Return the forward distance from `from` to `to` on the ringbuffer, taking wrap-around into account.
+///
+/// Note: The distance is zero both for `from == to` and a full lap around the ringbuffer, so
+/// the queue keeps track of its `length` instead of its end index.
+fn wrapping_distance<Index: WrappingSub>(from: Index, to: Index) -> Index {
+	to.wrapping_sub(&from)
+}
+
 /// Transient ringbuffer that sits on top of storage.
 pub struct BoundedDeque<Item, B, M, Index = DefaultIdx>
 where
@@ -102,6 +111,19 @@ where
 		self.length = Index::max(self.length, self.length.wrapping_add(&Index::from(1)));
 	}
 
+	/// Push multiple items onto the back of the queue, keeping their order.
+	///
+	/// + Behaves like calling `push_back` for every item: If the queue becomes full, items at the
+	///   front are written over. This includes items of the batch itself if it is larger than the
+	///   capacity of the queue, so only the last items of such a batch remain.
+	/// + Will insert the new items into storage, but will only update the bounds in memory, so
+	///   the bounds are written once on `commit`.
+	pub fn push_back_multiple<I: IntoIterator<Item = Item>>(&mut self, items: I) {
+		for item in items {
+			self.push_back(item);
+		}
+	}
+
 	/// Push an item onto the front of the queue.
 	/// 
 	/// + Will write over the item at the back if the queue is full.
@@ -141,6 +163,37 @@ where
 		item.into()
 	}
 
+	/// Return the item at the front of the queue without removing it.
+	///
+	/// Reads the item from storage, so changes to the returned item are not stored.
+	pub fn peek_front(&self) -> Option<Item> {
+		self.peek_at(Index::from(0))
+	}
+
+	/// Return the item `offset` positions behind the front of the queue without removing it.
+	///
+	/// + Returns `None` if `offset` is not less than the length of the queue.
+	/// + Reads the item from storage, so changes to the returned item are not stored.
+	pub fn peek_at(&self, offset: Index) -> Option<Item> {
+		if offset >= self.length {
+			return None;
+		}
+		Some(M::get(self.start.wrapping_add(&offset)))
+	}
+
+	/// Return whether the item at the storage `index` is part of the queue.
+	pub fn contains(&self, index: Index) -> bool {
+		wrapping_distance(self.start, index) < self.length
+	}
+
+	/// Return the number of items in the queue.
+	///
+	/// The length is tracked instead of computed from the bounds, so it is also correct after the
+	/// indices wrapped around (with the end index before `start`).
+	pub fn len(&self) -> Index {
+		self.length
+	}
+
 	/// Return whether to consider the queue empty.
 	pub fn is_empty(&self) -> bool {
 		self.length == Index::from(0)
@@ -173,12 +226,14 @@ mod tests {
 
 	use codec::{Decode, Encode};
 	use frame_support::{decl_module, decl_storage, impl_outer_origin, parameter_types, weights::Weight};
+	use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
 	use sp_core::H256;
 	use sp_runtime::{
 		testing::Header,
 		traits::{BlakeTwo256, IdentityLookup},
 		Perbill,
 	};
+	use std::collections::VecDeque;
 	use system;
 
 	impl_outer_origin! {
@@ -381,6 +436,47 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn push_back_multiple_matches_single_pushes() {
+		let items: Vec<SomeStruct> = (1..10).map(|i| SomeStruct { foo: i, bar: 2 * i }).collect();
+		let single = new_test_ext().execute_with(|| {
+			let mut queue = Queue::from_bounds(250, 2);
+			for item in items.clone() {
+				queue.push_back(item);
+			}
+			queue.commit();
+			let values: Vec<SomeStruct> =
+				(0..=TestIdx::max_value()).map(TestModule::get_test_value).collect();
+			(TestModule::get_test_range(), values)
+		});
+		new_test_ext().execute_with(|| {
+			let mut queue = Queue::from_bounds(250, 2);
+			queue.push_back_multiple(items.clone());
+			queue.commit();
+			let values: Vec<SomeStruct> =
+				(0..=TestIdx::max_value()).map(TestModule::get_test_value).collect();
+			assert_eq!((TestModule::get_test_range(), values), single);
+			assert_eq!(TestModule::get_test_range(), (250, 11));
+			assert_eq!(TestModule::get_test_value(252), SomeStruct { foo: 1, bar: 2 });
+			assert_eq!(TestModule::get_test_value(4), SomeStruct { foo: 9, bar: 18 });
+		});
+	}
+
+	#[test]
+	fn push_back_multiple_wraps_around() {
+		new_test_ext().execute_with(|| {
+			let mut queue = Queue::new();
+			// one more than the capacity of the queue
+			let count = TestIdx::max_value() as u64 + 1;
+			queue.push_back_multiple((1..=count).map(|i| SomeStruct { foo: 42, bar: i }));
+			queue.commit();
+			assert_eq!(TestModule::get_test_range(), (1, TestIdx::max_value()));
+			// the first item of the batch was written over
+			assert_eq!(queue.pop_front().map(|s| s.bar), Some(2));
+			assert_eq!(queue.pop_back().map(|s| s.bar), Some(count));
+		})
+	}
+
 	#[test]
 	fn simple_push_front() {
 		new_test_ext().execute_with(|| {
@@ -396,4 +492,173 @@ mod tests {
 			assert_eq!((start, length), (TestIdx::max_value() - 1, 2));
 		})
 	}
+
+	#[test]
+	fn len_across_wrap_around() {
+		new_test_ext().execute_with(|| {
+			let mut queue = Queue::from_bounds(TestIdx::max_value() - 2, 0);
+			assert_eq!(queue.len(), 0);
+			for i in 1..=6 {
+				queue.push_back(SomeStruct { foo: i, bar: i });
+				assert_eq!(queue.len(), i as TestIdx);
+			}
+			// the end index wrapped around and is before `start` now
+			assert!(queue.end() < queue.start);
+			assert_eq!(queue.len(), 6);
+
+			// the length is clamped to the capacity
+			for i in 0..(TestIdx::max_value() as u64) {
+				queue.push_back(SomeStruct { foo: i, bar: i });
+			}
+			assert_eq!(queue.len(), TestIdx::max_value());
+			queue.pop_front();
+			assert_eq!(queue.len(), TestIdx::max_value() - 1);
+		})
+	}
+
+	#[test]
+	fn peek() {
+		new_test_ext().execute_with(|| {
+			let mut queue = Queue::new();
+			assert_eq!(queue.peek_front(), None);
+			assert_eq!(queue.peek_at(0), None);
+
+			queue.push_back(SomeStruct { foo: 1, bar: 2 });
+			assert_eq!(queue.peek_front(), Some(SomeStruct { foo: 1, bar: 2 }));
+			assert_eq!(queue.peek_at(0), Some(SomeStruct { foo: 1, bar: 2 }));
+			assert_eq!(queue.peek_at(1), None);
+			// peeking does not remove the item
+			assert_eq!(queue.pop_front(), Some(SomeStruct { foo: 1, bar: 2 }));
+			assert_eq!(queue.peek_front(), None);
+		})
+	}
+
+	#[test]
+	fn peek_wrapped_around() {
+		new_test_ext().execute_with(|| {
+			let mut queue = Queue::from_bounds(TestIdx::max_value() - 1, 0);
+			for i in 0..4 {
+				queue.push_back(SomeStruct { foo: i, bar: i });
+			}
+			assert_eq!(queue.peek_front(), Some(SomeStruct { foo: 0, bar: 0 }));
+			// items behind the wrap-around
+			assert_eq!(queue.peek_at(2), Some(SomeStruct { foo: 2, bar: 2 }));
+			assert_eq!(queue.peek_at(3), Some(SomeStruct { foo: 3, bar: 3 }));
+			assert_eq!(queue.peek_at(4), None);
+			assert_eq!(queue.peek_at(TestIdx::max_value()), None);
+		})
+	}
+
+	#[test]
+	fn wrapping_distance_test() {
+		assert_eq!(wrapping_distance::<TestIdx>(3, 10), 7);
+		assert_eq!(wrapping_distance::<TestIdx>(5, 5), 0);
+		// wrapped around
+		assert_eq!(wrapping_distance::<TestIdx>(250, 4), 10);
+		assert_eq!(wrapping_distance::<TestIdx>(1, 0), TestIdx::max_value());
+		// a full lap is indistinguishable from no distance
+		let start: TestIdx = 42;
+		let full_end = start.wrapping_add(TestIdx::max_value()).wrapping_add(1);
+		assert_eq!(wrapping_distance(start, full_end), 0);
+	}
+
+	#[test]
+	fn contains_test() {
+		new_test_ext().execute_with(|| {
+			let queue = Queue::from_bounds(250, 10);
+			assert!(queue.contains(250));
+			assert!(queue.contains(255));
+			assert!(queue.contains(3));
+			assert!(!queue.contains(4));
+			assert!(!queue.contains(249));
+
+			let full = Queue::from_bounds(7, TestIdx::max_value());
+			assert!(full.contains(5));
+			// the free index before `start`
+			assert!(!full.contains(6));
+
+			let empty = Queue::from_bounds(7, 0);
+			assert!(!empty.contains(7));
+		})
+	}
+
+	/// Operation on the queue that is applied `n` times.
+	#[derive(Clone, Debug)]
+	enum Op {
+		PushBack(u8),
+		PushFront(u8),
+		PopBack(u8),
+		PopFront(u8),
+	}
+
+	impl Arbitrary for Op {
+		fn arbitrary<G: Gen>(g: &mut G) -> Op {
+			let n = u8::arbitrary(g);
+			match u8::arbitrary(g) % 4 {
+				0 => Op::PushBack(n),
+				1 => Op::PushFront(n),
+				2 => Op::PopBack(n),
+				_ => Op::PopFront(n),
+			}
+		}
+	}
+
+	#[test]
+	fn behaves_like_vec_deque_quickcheck() {
+		fn property(start: TestIdx, ops: Vec<Op>) -> TestResult {
+			new_test_ext().execute_with(|| {
+				// one index is always kept free to distinguish a full from an empty queue
+				let capacity = TestIdx::max_value() as usize;
+				let mut queue = Queue::from_bounds(start, 0);
+				let mut expected = VecDeque::new();
+				let mut next: u64 = 0;
+				for op in ops {
+					match op {
+						Op::PushBack(n) => {
+							for _ in 0..n {
+								next += 1;
+								queue.push_back(SomeStruct { foo: next, bar: 0 });
+								// a full queue overwrites the front item
+								if expected.len() == capacity {
+									expected.pop_front();
+								}
+								expected.push_back(next);
+							}
+						}
+						Op::PushFront(n) => {
+							for _ in 0..n {
+								next += 1;
+								queue.push_front(SomeStruct { foo: next, bar: 0 });
+								// a full queue overwrites the back item
+								if expected.len() == capacity {
+									expected.pop_back();
+								}
+								expected.push_front(next);
+							}
+						}
+						Op::PopBack(n) => {
+							for _ in 0..n {
+								assert_eq!(queue.pop_back().map(|s| s.foo), expected.pop_back());
+							}
+						}
+						Op::PopFront(n) => {
+							for _ in 0..n {
+								assert_eq!(queue.pop_front().map(|s| s.foo), expected.pop_front());
+							}
+						}
+					}
+				}
+				while let Some(foo) = expected.pop_front() {
+					assert_eq!(queue.pop_front().map(|s| s.foo), Some(foo));
+				}
+				assert!(queue.is_empty());
+
+				TestResult::passed()
+			})
+		}
+
+		QuickCheck::new()
+			.tests(100)
+			.quickcheck(property as fn(TestIdx, Vec<Op>) -> TestResult)
+	}
 }